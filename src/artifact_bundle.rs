@@ -0,0 +1,100 @@
+//! SwiftPM artifact bundle output for Rust executables.
+//!
+//! Workspaces that ship a Rust CLI as a SwiftPM build-tool plugin need it
+//! packaged as an `.artifactbundle`: a directory with an `info.json`
+//! declaring per-triple executable variants. The `artifact-bundle`
+//! subcommand builds a bin target for both macOS architectures — plugins
+//! run on the host Mac — and lays the bundle out so a `binaryTarget` can
+//! reference it directly.
+
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+
+use crate::build::profile_dir_name;
+use crate::project::Project;
+use crate::utils::{fs, ExecuteCommand};
+
+/// The rustc triples plugins can run on, with the SwiftPM spelling
+/// `info.json` wants.
+const MACOS_TRIPLES: &[(&str, &str)] = &[
+    ("aarch64-apple-darwin", "arm64-apple-macosx"),
+    ("x86_64-apple-darwin", "x86_64-apple-macosx"),
+];
+
+/// Build the workspace bin target named `bin` for both macOS architectures
+/// and package it into `<output_root>/<bin>.artifactbundle`.
+pub fn package_artifact_bundle(bin: &str, profile: &str) -> crate::Result<()> {
+    let run = || -> Result<()> {
+        let project = Project::from_current_dir()?;
+        let package = project
+            .metadata
+            .packages
+            .iter()
+            .filter(|package| project.metadata.workspace_members.contains(&package.id))
+            .find(|package| package.targets.iter().any(|t| t.is_bin() && t.name == bin))
+            .with_context(|| format!("No bin target named {bin} in the workspace"))?;
+        let version = package.version.to_string();
+
+        for (triple, _) in MACOS_TRIPLES {
+            let mut cmd = Command::new("cargo");
+            cmd.arg("build")
+                .args(["--package", &package.name])
+                .args(["--bin", bin])
+                .args(["--target", triple])
+                .args(["--profile", profile]);
+            if crate::utils::offline() {
+                cmd.arg("--offline");
+            }
+            cmd.args(crate::utils::cargo_lock_flags());
+            cmd.successful_output()?;
+        }
+
+        let bundle_dir = project.output_root().join(format!("{bin}.artifactbundle"));
+        fs::recreate_dir(&bundle_dir)?;
+        let mut variants = Vec::new();
+        for (triple, swift_triple) in MACOS_TRIPLES {
+            let built = project
+                .target_dir()
+                .join(triple)
+                .join(profile_dir_name(profile))
+                .join(bin);
+            if !built.exists() {
+                bail!("Expected a binary at {built} after the build");
+            }
+            let variant_path = format!("{bin}-{version}-{triple}/bin/{bin}");
+            let destination = bundle_dir.join(&variant_path);
+            let parent = destination.parent().expect("variant paths have a parent");
+            std::fs::create_dir_all(parent).with_context(|| format!("Can't create {parent}"))?;
+            // std::fs::copy preserves the executable bit.
+            std::fs::copy(&built, &destination)
+                .with_context(|| format!("Can't copy {built} to {destination}"))?;
+            variants.push(serde_json::json!({
+                "path": variant_path,
+                "supportedTriples": [swift_triple],
+            }));
+        }
+
+        let info = serde_json::json!({
+            "schemaVersion": "1.0",
+            "artifacts": {
+                bin: {
+                    "type": "executable",
+                    "version": version,
+                    "variants": variants,
+                },
+            },
+        });
+        let info_path = bundle_dir.join("info.json");
+        std::fs::write(&info_path, serde_json::to_string_pretty(&info)? + "\n")
+            .with_context(|| format!("Can't write {info_path}"))?;
+
+        println!("Packaged {bundle_dir}");
+        println!(
+            "Reference it with .binaryTarget(name: \"{bin}\", path: \"{}\")",
+            crate::spm::relative_to_root(&project, &bundle_dir)
+        );
+        Ok(())
+    };
+    run().map_err(crate::Error::from)
+}
@@ -0,0 +1,158 @@
+//! Bazel BUILD file emission.
+//!
+//! Monorepos on Bazel consume the SDK through `rules_apple` and
+//! `rules_swift`, and hand-written glue drifts whenever a wrapper module is
+//! added or renamed. The `generate-bazel` subcommand emits the matching
+//! BUILD targets: an `apple_static_xcframework_import` per XCFramework and a
+//! `swift_library` per bindings and wrapper module, with the same dependency
+//! wiring the generated `Package.swift` declares. Test targets and Swift
+//! compiler settings from `uniffi.toml` are not translated; Bazel projects
+//! declare those natively.
+
+use std::fmt;
+
+use anyhow::{Context, Result};
+use camino::Utf8Path;
+use rinja::Template;
+
+use crate::project::Project;
+use crate::spm::{
+    internal_target, relative_to_root, source_targets, vend_swift_source_code, SwiftTarget,
+};
+use crate::xcframework::FrameworkLayout;
+
+/// Generate `rules_swift`/`rules_apple` BUILD targets for the SDK, at
+/// `output` or the default `BUILD.bazel` under the workspace root. Requires
+/// the wrappers from a prior `build` run, since the rules glob them.
+pub fn generate_bazel(layout: FrameworkLayout, output: Option<&Utf8Path>) -> crate::Result<()> {
+    let run = || -> Result<()> {
+        let project = Project::from_current_dir()?;
+
+        let mut rules = Vec::new();
+        match layout {
+            FrameworkLayout::Merged => rules.push(BazelRule::XcframeworkImport {
+                name: project.ffi_module_name.clone(),
+                path: relative_to_root(&project, &project.xcframework_path()),
+            }),
+            FrameworkLayout::PerCrate => {
+                for package in &project.uniffi_packages {
+                    rules.push(BazelRule::XcframeworkImport {
+                        name: package.ffi_module_name(),
+                        path: relative_to_root(&project, &project.crate_xcframework_path(package)),
+                    });
+                }
+            }
+        }
+
+        for package in &project.uniffi_packages {
+            let swift_dir = if package.is_in_workspace(project.workspace_root()) {
+                package.swift_source_dir()
+            } else {
+                vend_swift_source_code(&project, package, false)?
+            };
+            let bindings = internal_target(&project, package, layout, false)?;
+            rules.push(swift_library(&project, &bindings, true));
+            let (wrappers, _tests) = source_targets(&project, package, &swift_dir)?;
+            for target in &wrappers {
+                rules.push(swift_library(&project, target, false));
+            }
+        }
+
+        let rendered = BuildFile { rules: &rules }
+            .render()
+            .context("Can't render BUILD.bazel")?;
+        let output_path = match output {
+            Some(path) => path.to_owned(),
+            None => project.workspace_root().join("BUILD.bazel"),
+        };
+        std::fs::write(&output_path, rendered)
+            .with_context(|| format!("Can't write {output_path}"))?;
+        println!("Generated {output_path}");
+        Ok(())
+    };
+    run().map_err(crate::Error::from)
+}
+
+/// Map an SPM source target onto a `swift_library`. The bindings library
+/// additionally carries the linker options from `link_libraries` and
+/// `link_frameworks`, mirroring where the manifest puts `linkerSettings`.
+fn swift_library(project: &Project, target: &SwiftTarget, bindings: bool) -> BazelRule {
+    let mut linkopts = Vec::new();
+    if bindings {
+        for library in &project.link_libraries {
+            linkopts.push(format!("-l{library}"));
+        }
+        for framework in &project.link_frameworks {
+            linkopts.push("-framework".to_string());
+            linkopts.push(framework.clone());
+        }
+    }
+    BazelRule::SwiftLibrary {
+        name: target.name.clone(),
+        path: target.path.clone(),
+        deps: target.dependencies.clone(),
+        linkopts,
+    }
+}
+
+enum BazelRule {
+    XcframeworkImport {
+        name: String,
+        path: String,
+    },
+    SwiftLibrary {
+        name: String,
+        path: String,
+        deps: Vec<String>,
+        linkopts: Vec<String>,
+    },
+}
+
+impl fmt::Display for BazelRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::XcframeworkImport { name, path } => write!(
+                f,
+                "apple_static_xcframework_import(\n    \
+                 name = \"{name}\",\n    \
+                 xcframework_imports = glob([\"{path}/**\"]),\n    \
+                 visibility = [\"//visibility:public\"],\n)"
+            ),
+            Self::SwiftLibrary {
+                name,
+                path,
+                deps,
+                linkopts,
+            } => {
+                write!(
+                    f,
+                    "swift_library(\n    \
+                     name = \"{name}\",\n    \
+                     module_name = \"{name}\",\n    \
+                     srcs = glob([\"{path}/**/*.swift\"]),\n"
+                )?;
+                if !deps.is_empty() {
+                    writeln!(f, "    deps = [")?;
+                    for dep in deps {
+                        writeln!(f, "        \":{dep}\",")?;
+                    }
+                    writeln!(f, "    ],")?;
+                }
+                if !linkopts.is_empty() {
+                    writeln!(f, "    linkopts = [")?;
+                    for opt in linkopts {
+                        writeln!(f, "        \"{opt}\",")?;
+                    }
+                    writeln!(f, "    ],")?;
+                }
+                write!(f, "    visibility = [\"//visibility:public\"],\n)")
+            }
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "BUILD.bazel", escape = "none")]
+struct BuildFile<'a> {
+    rules: &'a [BazelRule],
+}
@@ -0,0 +1,101 @@
+//! FFI overhead benchmarks.
+//!
+//! Hot-path regressions at the FFI boundary (extra copies, lock contention in
+//! the handle maps) don't show up in unit tests. `bench` builds the macOS
+//! slice in release mode and runs the XCTest benchmarks from the packages'
+//! `Tests` directories — `measure` blocks in test classes matching the filter
+//! — then pulls the averages out of the XCTest output into one summary.
+
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+
+use crate::build::{build, BuildOptions};
+use crate::events::Reporter;
+use crate::utils::ExecuteCommand;
+use crate::xcframework::ApplePlatform;
+
+/// Build the macOS slice in release and run the matching XCTest benchmarks.
+///
+/// `filter` is passed to `swift test --filter`; by convention benchmark
+/// classes are named `…Benchmarks` so plain unit tests stay out of the run.
+pub fn bench(filter: &str, reporter: &Reporter) -> crate::Result<()> {
+    let run = || -> Result<()> {
+        // Release matters twice over: debug FFI calls are dominated by
+        // assertion overhead, and consumers only ever ship release builds.
+        build(
+            &[ApplePlatform::MacOS],
+            "release",
+            &BuildOptions::default(),
+            reporter,
+        )?;
+
+        let output = Command::new("swift")
+            .args(["test", "-c", "release", "--filter", filter])
+            .successful_output()
+            .context("Benchmark run failed")?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+
+        let measurements = parse_measurements(&stdout);
+        if measurements.is_empty() {
+            bail!(
+                "No benchmarks matched `{filter}`. Add XCTest classes with \
+                 `measure` blocks to the packages' Tests directories."
+            );
+        }
+        println!("Benchmark results (average seconds):");
+        for (name, average) in measurements {
+            println!("  {average:>12.6}  {name}");
+        }
+        Ok(())
+    };
+    run().map_err(crate::Error::from)
+}
+
+/// Pull `(test name, average seconds)` pairs out of XCTest's measurement
+/// lines, which look like:
+///
+/// ```text
+/// Test Case '-[ApiTests.FFIBenchmarks testCallOverhead]' measured [Time, seconds] average: 0.000012, ...
+/// ```
+fn parse_measurements(output: &str) -> Vec<(String, f64)> {
+    let mut measurements = Vec::new();
+    for line in output.lines() {
+        let Some((prefix, rest)) = line.split_once("' measured [Time, seconds] average: ") else {
+            continue;
+        };
+        let Some((_, name)) = prefix.rsplit_once('[') else {
+            continue;
+        };
+        let name = name.trim_end_matches(']');
+        let Some(average) = rest
+            .split(',')
+            .next()
+            .and_then(|value| value.trim().parse::<f64>().ok())
+        else {
+            continue;
+        };
+        measurements.push((name.to_string(), average));
+    }
+    measurements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_measurements_extracts_name_and_average() {
+        let output = "Test Suite 'All tests' started\n\
+            Test Case '-[ApiTests.FFIBenchmarks testCallOverhead]' measured \
+            [Time, seconds] average: 0.000012, relative standard deviation: 1.2%\n\
+            Test Case '-[ApiTests.FFIBenchmarks testCallOverhead]' passed\n";
+        assert_eq!(
+            parse_measurements(output),
+            vec![(
+                "ApiTests.FFIBenchmarks testCallOverhead".to_string(),
+                0.000012
+            )]
+        );
+    }
+}
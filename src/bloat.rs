@@ -0,0 +1,162 @@
+//! Binary size analysis, in the spirit of cargo-bloat.
+//!
+//! For each built slice, `nm --size-sort` lists defined symbol sizes; we
+//! demangle the names and attribute each symbol to its top-level crate, so
+//! size growth can be traced to specific Rust dependencies.
+
+use std::collections::BTreeMap;
+use std::process::Command;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::build::{profile_dir_name, CrateType};
+use crate::error::Error;
+use crate::project::Project;
+use crate::utils::ExecuteCommand;
+use crate::xcframework::{ApplePlatform, Slice};
+
+/// Symbol sizes for one built slice, grouped by crate.
+#[derive(Serialize)]
+pub struct SliceReport {
+    pub target: String,
+    /// Sum of all defined symbol sizes, in bytes.
+    pub total_size: u64,
+    /// Largest first.
+    pub crates: Vec<CrateSize>,
+}
+
+#[derive(Serialize)]
+pub struct CrateSize {
+    pub crate_name: String,
+    pub size: u64,
+    pub symbols: usize,
+}
+
+/// Analyze the static libraries built for `platforms` and print a text table
+/// (or, with `json`, a JSON report) of symbol sizes per crate.
+pub fn bloat(platforms: &[ApplePlatform], profile: &str, json: bool) -> crate::Result<()> {
+    let run = || -> Result<()> {
+        let project = Project::from_current_dir()?;
+        let profile_dir_name = profile_dir_name(profile);
+
+        let mut reports = Vec::new();
+        for platform in platforms {
+            for target in platform.target_triples() {
+                let slice = Slice::create(&project, target, profile_dir_name, CrateType::Staticlib)?;
+                reports.push(analyze_slice(target, &slice)?);
+            }
+        }
+
+        if json {
+            println!("{}", serde_json::to_string_pretty(&reports)?);
+        } else {
+            for report in &reports {
+                print_report(report);
+            }
+        }
+        Ok(())
+    };
+    run().map_err(Error::from)
+}
+
+fn analyze_slice(target: &str, slice: &Slice) -> Result<SliceReport> {
+    let output = Command::new("nm")
+        .args(["--size-sort", "--radix=x", slice.library_path.as_str()])
+        .successful_output()?;
+    let stdout = String::from_utf8(output.stdout).context("nm produced non-UTF-8 output")?;
+
+    let mut crates: BTreeMap<String, CrateSize> = BTreeMap::new();
+    let mut total_size = 0;
+    for (symbol, size) in parse_nm_output(&stdout) {
+        total_size += size;
+        let crate_name = crate_of(&symbol);
+        let entry = crates.entry(crate_name.clone()).or_insert(CrateSize {
+            crate_name,
+            size: 0,
+            symbols: 0,
+        });
+        entry.size += size;
+        entry.symbols += 1;
+    }
+
+    let mut crates: Vec<CrateSize> = crates.into_values().collect();
+    crates.sort_by_key(|c| std::cmp::Reverse(c.size));
+    Ok(SliceReport {
+        target: target.to_string(),
+        total_size,
+        crates,
+    })
+}
+
+/// Parse `nm --size-sort --radix=x` output into (symbol, size) pairs.
+fn parse_nm_output(output: &str) -> Vec<(String, u64)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let size = u64::from_str_radix(fields.next()?, 16).ok()?;
+            let _symbol_type = fields.next()?;
+            let name = fields.next()?;
+            Some((name.to_string(), size))
+        })
+        .collect()
+}
+
+/// Attribute a (possibly mangled) symbol name to its top-level crate.
+fn crate_of(symbol: &str) -> String {
+    let demangled = rustc_demangle::demangle(symbol.trim_start_matches('_')).to_string();
+    match demangled.split("::").next() {
+        Some(first) if demangled.contains("::") => {
+            // Trait impls demangle as `<wp_api::Foo as core::fmt::Debug>::fmt`.
+            first.trim_start_matches('<').to_string()
+        }
+        _ => "[no crate]".to_string(),
+    }
+}
+
+fn print_report(report: &SliceReport) {
+    println!(
+        "{}: {} across {} crates",
+        report.target,
+        indicatif::HumanBytes(report.total_size),
+        report.crates.len()
+    );
+    for crate_size in &report.crates {
+        let percent = crate_size.size as f64 / report.total_size.max(1) as f64 * 100.0;
+        println!(
+            "  {:>9}  {:>5.1}%  {:>6} symbols  {}",
+            indicatif::HumanBytes(crate_size.size).to_string(),
+            percent,
+            crate_size.symbols,
+            crate_size.crate_name
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_nm_size_sort_output() {
+        let output = "\
+0000000000000018 t __ZN4core3ptr13drop_in_place17h0000000000000000E
+00000000000000a8 T _wp_api_ffi_func
+not-a-line
+";
+        let symbols = parse_nm_output(output);
+        assert_eq!(symbols.len(), 2);
+        assert_eq!(symbols[0].1, 0x18);
+        assert_eq!(symbols[1].0, "_wp_api_ffi_func");
+    }
+
+    #[test]
+    fn attributes_symbols_to_crates() {
+        assert_eq!(
+            crate_of("__ZN4core3ptr13drop_in_place17h0000000000000000E"),
+            "core"
+        );
+        assert_eq!(crate_of("_wp_api_ffi_func"), "[no crate]");
+    }
+}
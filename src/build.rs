@@ -0,0 +1,1127 @@
+use std::collections::BTreeMap;
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use rayon::prelude::*;
+use uniffi_bindgen::bindings::{generate_swift_bindings, SwiftBindingsOptions};
+
+use crate::deployment::DeploymentTargets;
+use crate::dsym::{extract_dsyms, upload_dsyms};
+use crate::error::Error;
+use crate::events::{BuildPhase, Reporter};
+use crate::project::{Project, UniffiPackage};
+use crate::spm::update_swift_wrappers;
+use crate::summary::{BuildSummary, TargetSummary};
+use crate::utils::{fs, ExecuteCommand};
+use crate::xcframework::{
+    create_crate_xcframeworks, create_xcframework, create_xcframework_variants, ApplePlatform,
+    FrameworkLayout,
+};
+
+/// Optional behaviors of [`build`], beyond platform and profile selection.
+#[derive(Default)]
+pub struct BuildOptions {
+    /// When set, extract dSYMs from the packaged libraries and upload each
+    /// one with this command (the bundle path is appended as the last
+    /// argument). See [`crate::dsym::DSYM_UPLOADER_ENV`].
+    pub dsym_uploader: Option<Vec<String>>,
+
+    /// Compile with `-C link-dead-code=off` and per-function/per-data
+    /// sections, so the consuming app's `-dead_strip` can drop unused code
+    /// from the static libraries.
+    pub strip_dead_code: bool,
+
+    /// Only build these UniFFI packages (all of them when empty). Useful in
+    /// monorepos with several heavy FFI crates.
+    pub packages: Vec<String>,
+
+    /// Clang module name for the generated FFI headers, when it must differ
+    /// from what uniffi-bindgen derives from the crate configuration.
+    pub module_name: Option<String>,
+
+    /// Filename for the generated module map (default: `module.modulemap`).
+    pub modulemap_filename: Option<String>,
+
+    /// Skip scanning dependencies for UniFFI metadata. Safe (and faster) when
+    /// every exported API lives in the top-level staticlib crates themselves.
+    pub metadata_no_deps: bool,
+
+    /// How to package the built libraries: one merged XCFramework (the
+    /// default) or one per UniFFI crate.
+    pub layout: FrameworkLayout,
+
+    /// Infer minimum OS versions from this `.xcodeproj` or xcconfig file and
+    /// export them to the Rust builds, instead of the toolchain defaults.
+    pub deployment_targets_from: Option<Utf8PathBuf>,
+
+    /// Fail instead of warning when a generated wrapper file was modified by
+    /// hand since the last run.
+    pub strict: bool,
+
+    /// Install the nightly toolchain and rust-src via rustup when a tier-3
+    /// platform needs them and they are missing, instead of failing.
+    pub install_missing_toolchain: bool,
+
+    /// Experimental: compile through `cargo zigbuild` and merge slices with
+    /// `llvm-lipo`, so Apple targets can be cross-compiled from a Linux host.
+    /// Needs cargo-zigbuild, zig, and `SDKROOT` pointing at an Apple SDK.
+    pub zigbuild: bool,
+
+    /// Lay out the XCFramework with our own assembler instead of
+    /// `xcodebuild -create-xcframework`, for machines without full Xcode.
+    /// Implied by `zigbuild`.
+    pub no_xcodebuild: bool,
+
+    /// Compile the generated bindings with `-emit-module-interface` and place
+    /// the `.swiftinterface`/`.swiftmodule` files into the XCFramework
+    /// slices, so binary-only consumers get module support without the
+    /// source wrapper targets.
+    pub emit_module_interface: bool,
+
+    /// Generate the Swift bindings by invoking this command (e.g.
+    /// `["cargo", "run", "-p", "my-bindgen", "--"]`) instead of the bundled
+    /// `uniffi_bindgen` library, so the project isn't locked to the uniffi
+    /// version this helper was compiled against. The command gets uniffi's
+    /// `generate --library` CLI arguments appended.
+    pub bindgen_command: Option<Vec<String>>,
+
+    /// Stamp each slice with `vtool -set-build-version` before assembly.
+    /// Tier-3 builds can produce object files without build-version load
+    /// commands, which makes xcodebuild mis-classify the library. The
+    /// minimum OS comes from `deployment_targets_from` or the deployment
+    /// target environment variables.
+    pub fix_build_version: bool,
+
+    /// Refuse to build unless a `Cargo.lock` exists, and pass `--locked` to
+    /// cargo so the build fails if the lockfile would change. Protects
+    /// release builds from git dependencies floating to a newer commit.
+    pub require_locked: bool,
+
+    /// Pass `--timings` to each cargo build and collect the HTML reports
+    /// under `<output_root>/<ffi_module_name>/timings/`, named per package
+    /// and target, for profiling slow (e.g. build-std) compilation steps.
+    pub cargo_timings: bool,
+
+    /// Keep building the remaining targets when one fails to compile (the
+    /// tier-3 slices can be flaky), report the failures at the end, and
+    /// package only the successful subset. The run still exits non-zero so
+    /// CI notices the gap.
+    pub keep_going: bool,
+
+    /// Print the end-of-run summary as JSON instead of a console table.
+    pub json_summary: bool,
+
+    /// Package both linkage variants from the same cargo builds:
+    /// `<ffi>-static.xcframework` from the static archives and
+    /// `<ffi>-dynamic.xcframework` from the cdylibs, with the generated
+    /// Package.swift choosing via the `UNIFFI_LINKAGE` environment variable.
+    /// Needs `crate-type = ["staticlib", "cdylib"]` and the merged layout.
+    pub linkage_variants: bool,
+
+    /// Build only the arm64 simulator and arm64 macOS slices, so the inner
+    /// development loop isn't paying for device, Intel, watch, and TV
+    /// builds. The resulting XCFramework runs in the iOS simulator and on
+    /// Apple Silicon Macs, nowhere else.
+    pub dev_fast: bool,
+
+    /// First pipeline stage to run (earlier ones are assumed done). Lets a
+    /// failed packaging run resume without redoing the compilation.
+    pub from: Option<BuildStage>,
+
+    /// Last pipeline stage to run; later ones are skipped.
+    pub until: Option<BuildStage>,
+}
+
+/// The target triples `--dev-fast` keeps: the iOS simulator and the host
+/// architecture of every Apple Silicon development machine.
+const DEV_FAST_TARGETS: &[&str] = &["aarch64-apple-ios-sim", "aarch64-apple-darwin"];
+
+impl BuildOptions {
+    /// Whether `stage` falls inside the requested `--from`/`--until` range.
+    fn stage_enabled(&self, stage: BuildStage) -> bool {
+        self.from.is_none_or(|from| stage >= from)
+            && self.until.is_none_or(|until| stage <= until)
+    }
+
+    /// Whether `target` should be built, honoring the `--dev-fast` preset.
+    fn target_enabled(&self, target: &str) -> bool {
+        !self.dev_fast || DEV_FAST_TARGETS.contains(&target)
+    }
+}
+
+/// The stages of the build pipeline, in execution order.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
+pub enum BuildStage {
+    /// Compile the UniFFI crates for every target triple.
+    RustBuild,
+    /// Generate Swift bindings, headers, and module maps.
+    Bindings,
+    /// Assemble the XCFramework(s) and upload dSYMs.
+    Package,
+    /// Rewrite the generated wrappers into the swift-wrapper directory.
+    Wrappers,
+}
+
+impl BuildStage {
+    fn name(self) -> &'static str {
+        match self {
+            Self::RustBuild => "rust-build",
+            Self::Bindings => "bindings",
+            Self::Package => "package",
+            Self::Wrappers => "wrappers",
+        }
+    }
+}
+
+/// Build every UniFFI package for `platforms`, generate the Swift bindings,
+/// assemble the XCFramework, and refresh the wrapper sources.
+///
+/// Progress is reported through `reporter`; pass [`Reporter::silent`] to
+/// discard events.
+pub fn build(
+    platforms: &[ApplePlatform],
+    profile: &str,
+    options: &BuildOptions,
+    reporter: &Reporter,
+) -> crate::Result<()> {
+    let run = || -> Result<()> {
+        // Checked before reading metadata: `cargo metadata` would silently
+        // generate the missing lockfile we are supposed to reject.
+        if options.require_locked && !Utf8Path::new("Cargo.lock").exists() {
+            bail!("--require-locked needs a committed Cargo.lock at the workspace root");
+        }
+        let mut project = Project::from_current_dir()?;
+        // An external bindgen brings its own uniffi version, so the bundled
+        // compatibility check doesn't apply.
+        if options.bindgen_command.is_none() {
+            check_uniffi_versions(&project)?;
+        }
+        project.select_packages(&options.packages)?;
+        project.build(platforms, profile, options, reporter)
+    };
+    run().map_err(Error::from)
+}
+
+/// Regenerate the Swift bindings, headers, module map, and processed
+/// wrappers from libraries that are already built, without running cargo.
+/// Useful when only the post-processing (templates, plugins, external
+/// types) changed: the wrappers are rewritten even when the bindings were
+/// already up to date, since the library hash can't see template edits.
+pub fn regenerate_bindings(
+    platform: ApplePlatform,
+    profile: &str,
+    options: &BuildOptions,
+    reporter: &Reporter,
+) -> crate::Result<()> {
+    let run = || -> Result<()> {
+        let mut project = Project::from_current_dir()?;
+        project.select_packages(&options.packages)?;
+        // One slice is enough: the generated sources are identical across
+        // targets, and the wrappers are derived from the first one anyway.
+        let target = platform.target_triples()[0];
+        let profile_dir = profile_dir_name(profile);
+        reporter.phase_started(BuildPhase::Bindings, 1);
+        match options.layout {
+            FrameworkLayout::Merged => {
+                generate_bindings(&project, target, profile_dir, options)?;
+            }
+            FrameworkLayout::PerCrate => {
+                for package in &project.uniffi_packages {
+                    generate_crate_bindings(&project, package, target, profile_dir, options)?;
+                }
+            }
+        }
+        reporter.step_finished(BuildPhase::Bindings, target);
+        reporter.phase_finished(BuildPhase::Bindings);
+        update_swift_wrappers(&project, target, options.layout, options.strict, reporter)
+    };
+    run().map_err(Error::from)
+}
+
+pub(crate) trait BuildExtensions {
+    fn build(
+        &self,
+        platforms: &[ApplePlatform],
+        profile: &str,
+        options: &BuildOptions,
+        reporter: &Reporter,
+    ) -> Result<()>;
+}
+
+impl BuildExtensions for Project {
+    fn build(
+        &self,
+        platforms: &[ApplePlatform],
+        profile: &str,
+        options: &BuildOptions,
+        reporter: &Reporter,
+    ) -> Result<()> {
+        let profile_dir_name = profile_dir_name(profile);
+        let deployment_targets = options
+            .deployment_targets_from
+            .as_deref()
+            .map(DeploymentTargets::from_path)
+            .transpose()?;
+        let targets: Vec<&str> = platforms
+            .iter()
+            .flat_map(ApplePlatform::target_triples)
+            .filter(|target| options.target_enabled(target))
+            .collect();
+        if targets.is_empty() {
+            bail!("--dev-fast builds no slice for the selected platforms");
+        }
+
+        if platforms.iter().any(ApplePlatform::requires_nightly_toolchain) {
+            ensure_nightly_toolchain(options.install_missing_toolchain)?;
+        }
+        if options.zigbuild {
+            // Cross-compiling from Linux: no Xcode to version-check, and the
+            // linker needs an SDK it can't discover through xcrun.
+            if std::env::var_os("SDKROOT").is_none() {
+                bail!(
+                    "zigbuild needs SDKROOT to point at an Apple SDK \
+                     (e.g. an extracted MacOSX.sdk)"
+                );
+            }
+            crate::utils::set_use_llvm_tools(true);
+        } else {
+            crate::xcode::check_xcode_version(platforms)?;
+            crate::xcode::check_simulator_runtimes(platforms)?;
+        }
+
+        let mut failed_targets: Vec<(&str, anyhow::Error)> = Vec::new();
+        let mut build_durations: BTreeMap<&str, std::time::Duration> = BTreeMap::new();
+        if options.stage_enabled(BuildStage::RustBuild) {
+            reporter.phase_started(
+                BuildPhase::RustBuild,
+                targets.len() * self.uniffi_packages.len(),
+            );
+            for platform in platforms {
+                'targets: for target in platform.target_triples() {
+                    if !options.target_enabled(target) {
+                        continue;
+                    }
+                    let started = std::time::Instant::now();
+                    for package in &self.uniffi_packages {
+                        match build_uniffi_package(
+                            self,
+                            package,
+                            target,
+                            *platform,
+                            profile,
+                            options,
+                            deployment_targets.as_ref(),
+                        ) {
+                            Ok(()) => reporter.step_finished(
+                                BuildPhase::RustBuild,
+                                format!("{} ({target})", package.package.name),
+                            ),
+                            Err(error) if options.keep_going => {
+                                eprintln!("Warning: {target} failed to build, continuing");
+                                failed_targets.push((target, error));
+                                build_durations.insert(target, started.elapsed());
+                                continue 'targets;
+                            }
+                            Err(error) => return Err(error),
+                        }
+                    }
+                    build_durations.insert(target, started.elapsed());
+                }
+            }
+            reporter.phase_finished(BuildPhase::RustBuild);
+        } else {
+            check_recorded_stage(self, BuildStage::RustBuild, profile, &targets);
+        }
+        let all_targets = targets.clone();
+
+        // The later stages package whatever did build; assembling an
+        // XCFramework from the successful subset is the point of --keep-going.
+        let targets: Vec<&str> = targets
+            .into_iter()
+            .filter(|target| failed_targets.iter().all(|(failed, _)| failed != target))
+            .collect();
+        if targets.is_empty() {
+            bail!("Every target failed to build");
+        }
+        if options.stage_enabled(BuildStage::RustBuild) {
+            record_stage(self, BuildStage::RustBuild, profile, &targets)?;
+        }
+
+        // Skipping the bindings stage means the wrappers can't assume the
+        // bindings are fresh, so a requested wrappers stage always runs.
+        let mut regenerated = true;
+        let mut bindings_regenerated: BTreeMap<&str, bool> = BTreeMap::new();
+        if options.stage_enabled(BuildStage::Bindings) {
+            // Binding generation is independent per target; fan it out over
+            // the rayon pool (bounded by the CPU count).
+            reporter.phase_started(BuildPhase::Bindings, targets.len());
+            bindings_regenerated = targets
+                .par_iter()
+                .map(|target| {
+                    let mut target_regenerated = false;
+                    match options.layout {
+                        FrameworkLayout::Merged => {
+                            target_regenerated =
+                                generate_bindings(self, target, profile_dir_name, options)?;
+                        }
+                        FrameworkLayout::PerCrate => {
+                            for package in &self.uniffi_packages {
+                                if generate_crate_bindings(
+                                    self,
+                                    package,
+                                    target,
+                                    profile_dir_name,
+                                    options,
+                                )? {
+                                    target_regenerated = true;
+                                }
+                            }
+                        }
+                    }
+                    reporter.step_finished(BuildPhase::Bindings, *target);
+                    Ok::<_, anyhow::Error>((*target, target_regenerated))
+                })
+                .collect::<Result<_>>()?;
+            regenerated = bindings_regenerated.values().any(|regenerated| *regenerated);
+            reporter.phase_finished(BuildPhase::Bindings);
+            record_stage(self, BuildStage::Bindings, profile, &targets)?;
+        } else {
+            check_recorded_stage(self, BuildStage::Bindings, profile, &targets);
+        }
+
+        let mut outputs = Vec::new();
+        if options.stage_enabled(BuildStage::Package) {
+            let xcframeworks = match (options.linkage_variants, options.layout) {
+                (true, FrameworkLayout::Merged) => create_xcframework_variants(
+                    self,
+                    &targets,
+                    profile_dir_name,
+                    options,
+                    reporter,
+                )?,
+                (true, FrameworkLayout::PerCrate) => {
+                    bail!("--linkage-variants only supports the merged layout")
+                }
+                (false, FrameworkLayout::Merged) => {
+                    vec![create_xcframework(self, &targets, profile_dir_name, options, reporter)?]
+                }
+                (false, FrameworkLayout::PerCrate) => {
+                    create_crate_xcframeworks(self, &targets, profile_dir_name, options, reporter)?
+                }
+            };
+
+            if let Some(uploader) = &options.dsym_uploader {
+                for xcframework in &xcframeworks {
+                    let dsyms = extract_dsyms(self, xcframework)?;
+                    upload_dsyms(uploader, &dsyms)?;
+                }
+            }
+            record_stage(self, BuildStage::Package, profile, &targets)?;
+            outputs = xcframeworks;
+        }
+
+        if options.stage_enabled(BuildStage::Wrappers) {
+            let first_target = targets
+                .first()
+                .expect("at least one platform is always requested");
+            // The wrappers derive purely from the generated bindings: when
+            // every target's bindings were up to date, so are they.
+            if regenerated || !self.swift_wrapper_dir().exists() {
+                update_swift_wrappers(self, first_target, options.layout, options.strict, reporter)?;
+            } else {
+                println!("Bindings unchanged; keeping the existing Swift wrappers");
+            }
+            record_stage(self, BuildStage::Wrappers, profile, &targets)?;
+        }
+
+        let summary = BuildSummary {
+            targets: all_targets
+                .iter()
+                .map(|target| {
+                    let failed = failed_targets.iter().any(|(failed, _)| failed == target);
+                    TargetSummary {
+                        target: target.to_string(),
+                        status: if failed { "failed" } else { "ok" },
+                        duration_secs: build_durations
+                            .get(target)
+                            .map(std::time::Duration::as_secs_f64),
+                        artifact_bytes: if failed {
+                            None
+                        } else {
+                            artifact_bytes(self, target, profile_dir_name)
+                        },
+                        bindings_cache: bindings_regenerated
+                            .get(target)
+                            .map(|regenerated| if *regenerated { "miss" } else { "hit" }),
+                    }
+                })
+                .collect(),
+            outputs,
+        };
+        summary.print(options.json_summary)?;
+
+        if !failed_targets.is_empty() {
+            let mut message = String::from(
+                "The outputs cover only the successful targets; these failed to build:\n",
+            );
+            for (target, error) in &failed_targets {
+                message.push_str(&format!("  {target}: {error:#}\n"));
+            }
+            bail!("{}", message.trim_end());
+        }
+        Ok(())
+    }
+}
+
+/// Total size of `target`'s built static libraries, when they can all be
+/// resolved. Purely informational, so failures turn into a blank cell.
+fn artifact_bytes(project: &Project, target: &str, profile_dir_name: &str) -> Option<u64> {
+    let library_dir = project.target_dir().join(target).join(profile_dir_name);
+    let mut total = 0;
+    for package in &project.uniffi_packages {
+        let library = find_library(&library_dir, Some(package), CrateType::Staticlib).ok()?;
+        total += std::fs::metadata(library).ok()?.len();
+    }
+    Some(total)
+}
+
+/// File recording which stage last completed, and with what profile and
+/// target set, so resumed runs can tell when they'd consume stale inputs.
+fn stage_state_path(project: &Project) -> Utf8PathBuf {
+    project
+        .output_root()
+        .join(&project.ffi_module_name)
+        .join(".build-stages")
+}
+
+/// One stage's state line: the profile and target set it last completed for.
+fn stage_descriptor(profile: &str, targets: &[&str]) -> String {
+    format!("{profile} {}", targets.join(","))
+}
+
+fn record_stage(
+    project: &Project,
+    stage: BuildStage,
+    profile: &str,
+    targets: &[&str],
+) -> Result<()> {
+    let path = stage_state_path(project);
+    let mut stages: std::collections::BTreeMap<String, String> = std::fs::read_to_string(&path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let (stage, descriptor) = line.split_once(": ")?;
+            Some((stage.to_string(), descriptor.to_string()))
+        })
+        .collect();
+    stages.insert(stage.name().to_string(), stage_descriptor(profile, targets));
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).with_context(|| format!("Can't create {parent}"))?;
+    }
+    let contents: String = stages
+        .iter()
+        .map(|(stage, descriptor)| format!("{stage}: {descriptor}\n"))
+        .collect();
+    std::fs::write(&path, contents).with_context(|| format!("Can't write {path}"))?;
+    Ok(())
+}
+
+/// Warn when a stage skipped via `--from` hasn't previously completed with
+/// the same profile and target set. Only a warning: the inputs may well have
+/// been produced elsewhere, e.g. downloaded from CI matrix jobs.
+fn check_recorded_stage(project: &Project, stage: BuildStage, profile: &str, targets: &[&str]) {
+    let expected = stage_descriptor(profile, targets);
+    let recorded = std::fs::read_to_string(stage_state_path(project))
+        .ok()
+        .and_then(|contents| {
+            contents.lines().find_map(|line| {
+                let (name, descriptor) = line.split_once(": ")?;
+                (name == stage.name()).then(|| descriptor.to_string())
+            })
+        });
+    match recorded {
+        Some(descriptor) if descriptor == expected => {}
+        Some(descriptor) => eprintln!(
+            "Warning: skipping the {} stage, which last completed for `{descriptor}` \
+             rather than `{expected}`",
+            stage.name()
+        ),
+        None => eprintln!(
+            "Warning: skipping the {} stage, which has no recorded run for this workspace",
+            stage.name()
+        ),
+    }
+}
+
+/// Run `cargo build` for one package and target triple.
+///
+/// By default `panic="abort"` keeps unwinding machinery out of the static
+/// libraries and `debug=true` preserves debug info for dSYM extraction in
+/// release builds; both overrides can be disabled in `uniffi.toml` (the
+/// `panic_abort` and `force_debug_info` keys).
+pub(crate) fn build_uniffi_package(
+    project: &Project,
+    package: &UniffiPackage,
+    target: &str,
+    platform: ApplePlatform,
+    profile: &str,
+    options: &BuildOptions,
+    deployment_targets: Option<&DeploymentTargets>,
+) -> Result<()> {
+    let mut cmd = Command::new("cargo");
+    // cargo-zigbuild is a drop-in `build` replacement that routes linking
+    // through zig's bundled clang, including for nightly -Z build-std.
+    let build_subcommand = if options.zigbuild { "zigbuild" } else { "build" };
+    if platform.requires_nightly_toolchain() {
+        cmd.args(["+nightly", build_subcommand, "-Z", "build-std"]);
+    } else {
+        cmd.arg(build_subcommand);
+    }
+    cmd.args(["--package", &package.package.name])
+        .args(["--target", target])
+        .args(["--profile", profile]);
+    if crate::utils::offline() {
+        cmd.arg("--offline");
+    }
+    cmd.args(crate::utils::cargo_lock_flags());
+    if options.require_locked {
+        cmd.arg("--locked");
+    }
+    if options.cargo_timings {
+        cmd.arg("--timings");
+    }
+    if project.panic_abort {
+        cmd.args(["--config", &format!("profile.{profile}.panic=\"abort\"")]);
+    }
+    if project.force_debug_info {
+        cmd.args(["--config", &format!("profile.{profile}.debug=true")]);
+    }
+    if let Some(mode) = &project.split_debuginfo {
+        cmd.args([
+            "--config",
+            &format!("profile.{profile}.split-debuginfo=\"{mode}\""),
+        ]);
+    }
+    // Configured profile overrides, platform-wide section first so the more
+    // specific per-triple section can override individual settings.
+    let mut profile_overrides: BTreeMap<&str, &toml::Value> = BTreeMap::new();
+    for section in [platform.name(), target] {
+        if let Some(settings) = project.profile_overrides.get(section) {
+            for (key, value) in settings {
+                profile_overrides.insert(key, value);
+            }
+        }
+    }
+    for (key, value) in profile_overrides {
+        cmd.args(["--config", &format!("profile.{profile}.{key}={value}")]);
+    }
+    if let Some(targets) = deployment_targets {
+        // cc and rustc read the minimum OS version for the Apple linker from
+        // these variables.
+        for (name, version) in targets.env_vars() {
+            cmd.env(name, version);
+        }
+    }
+    if let Some(sdkroot) = crate::xcode::sdkroot_for_target(target)? {
+        cmd.env("SDKROOT", sdkroot);
+    }
+    let mut rustflags = std::env::var("RUSTFLAGS").unwrap_or_default();
+    if options.strip_dead_code {
+        // Per-function/per-data sections let the consumer's `-dead_strip`
+        // discard unused code at app link time.
+        rustflags.push_str(
+            " -Clink-dead-code=off -Cllvm-args=-function-sections -Cllvm-args=-data-sections",
+        );
+    }
+    // Configured environment, platform-wide section first so the more
+    // specific per-triple section can override individual variables.
+    for section in [platform.name(), target] {
+        let Some(vars) = project.build_env.get(section) else {
+            continue;
+        };
+        for (name, value) in vars {
+            if name == "RUSTFLAGS" {
+                rustflags.push(' ');
+                rustflags.push_str(value);
+            } else {
+                cmd.env(name, value);
+            }
+        }
+    }
+    if !rustflags.trim().is_empty() {
+        cmd.env("RUSTFLAGS", rustflags.trim());
+    }
+    // The JSON messages name the exact artifacts this build produced, so
+    // later stages don't have to guess from directory contents.
+    cmd.arg("--message-format=json-render-diagnostics");
+    let output = match cmd.successful_output() {
+        Ok(output) => output,
+        Err(error) => {
+            // cargo reports a missing `+nightly` toolchain as a command
+            // failure; surface it as the dedicated error instead.
+            if platform.requires_nightly_toolchain()
+                && command_failed_stderr(&error, "not installed")
+            {
+                return Err(Error::MissingToolchain {
+                    target: target.to_string(),
+                }
+                .into());
+            }
+            return Err(error);
+        }
+    };
+    record_artifacts(
+        &project
+            .target_dir()
+            .join(target)
+            .join(profile_dir_name(profile)),
+        &package.package.name,
+        &String::from_utf8_lossy(&output.stdout),
+    )?;
+    if options.cargo_timings {
+        collect_timing_report(project, package, target)?;
+    }
+    Ok(())
+}
+
+/// Move the `cargo-timing.html` the build just wrote into the artifacts
+/// directory under a per-package, per-target name, before the next cargo
+/// invocation overwrites it.
+fn collect_timing_report(project: &Project, package: &UniffiPackage, target: &str) -> Result<()> {
+    let report = project.target_dir().join("cargo-timings/cargo-timing.html");
+    if !report.exists() {
+        // Nothing was compiled (everything cached), so cargo wrote no report.
+        return Ok(());
+    }
+    let timings_dir = project
+        .output_root()
+        .join(&project.ffi_module_name)
+        .join("timings");
+    std::fs::create_dir_all(&timings_dir)
+        .with_context(|| format!("Can't create {timings_dir}"))?;
+    let destination = timings_dir.join(format!("{}-{target}.html", package.package.name));
+    std::fs::rename(&report, &destination)
+        .with_context(|| format!("Can't move {report} to {destination}"))?;
+    println!("Timing report: {destination}");
+    Ok(())
+}
+
+/// File recording the exact artifact paths cargo reported, kept next to the
+/// libraries in `target/<triple>/<profile>/`. Scanning that directory for
+/// `*.a` misbehaves as soon as a dependency also emits a staticlib or a stale
+/// archive lingers from a renamed crate; cargo's JSON messages name the
+/// artifact exactly.
+const ARTIFACTS_FILE: &str = ".rust-artifacts";
+
+/// The `(crate_type, path)` artifacts for `package_name` in a cargo build's
+/// `--message-format=json` output. Crate types are classified from the file
+/// extension, since `filenames` and `target.kind` aren't ordered reliably.
+fn parse_artifact_messages(stdout: &str, package_name: &str) -> Vec<(&'static str, Utf8PathBuf)> {
+    let wanted = package_name.replace('-', "_");
+    let mut artifacts = Vec::new();
+    for line in stdout.lines() {
+        let Ok(message) = serde_json::from_str::<serde_json::Value>(line) else {
+            continue;
+        };
+        if message["reason"] != "compiler-artifact" {
+            continue;
+        }
+        let name = message["target"]["name"].as_str().unwrap_or_default();
+        if name.replace('-', "_") != wanted {
+            continue;
+        }
+        for filename in message["filenames"].as_array().into_iter().flatten() {
+            let Some(path) = filename.as_str() else {
+                continue;
+            };
+            let path = Utf8PathBuf::from(path);
+            let crate_type = match path.extension() {
+                Some("a") => "staticlib",
+                Some("dylib") => "cdylib",
+                _ => continue,
+            };
+            artifacts.push((crate_type, path));
+        }
+    }
+    artifacts
+}
+
+/// Merge the artifacts of one cargo run into the record file, keyed by crate
+/// type and package so rebuilding one package doesn't forget the others.
+fn record_artifacts(library_dir: &Utf8Path, package_name: &str, stdout: &str) -> Result<()> {
+    let artifacts = parse_artifact_messages(stdout, package_name);
+    if artifacts.is_empty() {
+        // Everything was cached: cargo emits no artifact message, and the
+        // previous run's record still holds.
+        return Ok(());
+    }
+    let path = library_dir.join(ARTIFACTS_FILE);
+    let mut entries: std::collections::BTreeMap<String, String> = std::fs::read_to_string(&path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, ' ');
+            let key = format!("{} {}", fields.next()?, fields.next()?);
+            Some((key, fields.next()?.to_string()))
+        })
+        .collect();
+    for (crate_type, artifact) in artifacts {
+        entries.insert(
+            format!("{crate_type} {package_name}"),
+            artifact.to_string(),
+        );
+    }
+    let contents: String = entries
+        .iter()
+        .map(|(key, artifact)| format!("{key} {artifact}\n"))
+        .collect();
+    std::fs::write(&path, contents).with_context(|| format!("Can't write {path}"))?;
+    Ok(())
+}
+
+/// The library flavors a UniFFI crate can emit. A crate listing
+/// `crate-type = ["staticlib", "cdylib", "lib"]` produces both a `.a` and a
+/// `.dylib`; which one a consumer wants depends on the output mode (static
+/// XCFramework vs dynamic framework), never on what happens to be on disk.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CrateType {
+    Staticlib,
+    Cdylib,
+}
+
+impl CrateType {
+    /// The crate-type name cargo uses, also the record file's key.
+    fn name(self) -> &'static str {
+        match self {
+            Self::Staticlib => "staticlib",
+            Self::Cdylib => "cdylib",
+        }
+    }
+
+    pub(crate) fn extension(self) -> &'static str {
+        match self {
+            Self::Staticlib => "a",
+            Self::Cdylib => "dylib",
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            Self::Staticlib => "static library",
+            Self::Cdylib => "dynamic library",
+        }
+    }
+}
+
+/// Resolve the `crate_type` library built into `library_dir`, for one package
+/// or — with `None` — for the whole merged build. Prefers the artifact record
+/// the build wrote; libraries produced without one (older helper versions,
+/// hand-run cargo) fall back to the historical directory scan.
+pub(crate) fn find_library(
+    library_dir: &Utf8Path,
+    package: Option<&UniffiPackage>,
+    crate_type: CrateType,
+) -> Result<Utf8PathBuf> {
+    let recorded: Vec<Utf8PathBuf> = std::fs::read_to_string(library_dir.join(ARTIFACTS_FILE))
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, ' ');
+            if fields.next()? != crate_type.name() {
+                return None;
+            }
+            let name = fields.next()?;
+            if package.is_some_and(|package| package.package.name.as_str() != name) {
+                return None;
+            }
+            Some(Utf8PathBuf::from(fields.next()?))
+        })
+        .collect();
+    match recorded.as_slice() {
+        [library] if library.exists() => return Ok(library.clone()),
+        [] | [_] => {} // No (usable) record; fall back to scanning.
+        _ => bail!(
+            "cargo reported multiple {}s in {library_dir}: {recorded:?}. \
+             Restrict the build with --packages or use the per-crate layout.",
+            crate_type.describe()
+        ),
+    }
+    if let Some(package) = package {
+        let mut library = library_dir.join(package.library_file_name());
+        library.set_extension(crate_type.extension());
+        if !library.exists() {
+            bail!("No {} at {library}. {}", crate_type.describe(), BUILD_HINT);
+        }
+        return Ok(library);
+    }
+    let libraries = fs::files_with_extension(library_dir, crate_type.extension())?;
+    match libraries.as_slice() {
+        [library] => Ok(library.clone()),
+        [] => bail!(
+            "No {} found in {library_dir}. {}",
+            crate_type.describe(),
+            BUILD_HINT
+        ),
+        _ => bail!(
+            "Expected exactly one {} in {library_dir}, found {libraries:?}",
+            crate_type.describe()
+        ),
+    }
+}
+
+/// Shared tail for "library not found" errors: the causes are the same for
+/// both flavors, but only a cdylib can be missing because of `crate-type`.
+const BUILD_HINT: &str =
+    "Did the build succeed, and does the crate's `crate-type` list what this output mode needs?";
+
+/// The `uniffi` major/minor the bundled `uniffi_bindgen` generates
+/// scaffolding for. Must track the dependency version in `Cargo.toml`.
+const BUNDLED_UNIFFI_VERSION: (u64, u64) = (0, 32);
+
+/// Check that every `uniffi` crate the workspace resolved is compatible with
+/// the bundled bindgen. A mismatch only surfaces at runtime as a scaffolding
+/// checksum error, so fail here with an actionable message instead.
+fn check_uniffi_versions(project: &Project) -> Result<()> {
+    let (major, minor) = BUNDLED_UNIFFI_VERSION;
+    for package in &project.metadata.packages {
+        if package.name.as_str() != "uniffi" {
+            continue;
+        }
+        if package.version.major != major || package.version.minor != minor {
+            bail!(
+                "The workspace depends on uniffi {}, but this tool bundles \
+                 uniffi_bindgen {major}.{minor}: the generated scaffolding would \
+                 mismatch at runtime. Pin uniffi to {major}.{minor}.x or upgrade \
+                 uniffi-swift-helper.",
+                package.version
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Verify that the nightly toolchain and its rust-src component are present
+/// before a tier-3 build needs them: `-Z build-std` fails with an opaque
+/// error otherwise. With `install`, missing pieces are installed via rustup.
+fn ensure_nightly_toolchain(install: bool) -> Result<()> {
+    if Command::new("rustup")
+        .args(["run", "nightly", "rustc", "--version"])
+        .successful_output()
+        .is_err()
+    {
+        if !install {
+            bail!(
+                "tvOS and watchOS need the nightly toolchain for -Z build-std, \
+                 but it is not installed. Run `rustup toolchain install nightly` \
+                 or pass --install-missing-toolchain."
+            );
+        }
+        Command::new("rustup")
+            .args(["toolchain", "install", "nightly"])
+            .successful_output()?;
+    }
+
+    let components = Command::new("rustup")
+        .args(["component", "list", "--installed", "--toolchain", "nightly"])
+        .successful_output()?;
+    if String::from_utf8_lossy(&components.stdout)
+        .lines()
+        .any(|line| line.starts_with("rust-src"))
+    {
+        return Ok(());
+    }
+    if !install {
+        bail!(
+            "-Z build-std needs the rust-src component of the nightly toolchain. \
+             Run `rustup component add rust-src --toolchain nightly` \
+             or pass --install-missing-toolchain."
+        );
+    }
+    Command::new("rustup")
+        .args(["component", "add", "rust-src", "--toolchain", "nightly"])
+        .successful_output()?;
+    Ok(())
+}
+
+/// Whether `error` is a [`Error::CommandFailed`] whose stderr mentions `needle`.
+fn command_failed_stderr(error: &anyhow::Error, needle: &str) -> bool {
+    matches!(
+        error.downcast_ref::<Error>(),
+        Some(Error::CommandFailed { stderr, .. }) if stderr.contains(needle)
+    )
+}
+
+/// Generate Swift sources, C headers, and the module map for the library
+/// built for `target`, into `target/<triple>/swift-bindings`.
+///
+/// Regeneration is skipped — returning `false` — when the library and the
+/// binding options are unchanged since the last run, so no-op rebuilds don't
+/// pay for uniffi-bindgen scanning the whole archive again.
+pub(crate) fn generate_bindings(
+    project: &Project,
+    target: &str,
+    profile_dir_name: &str,
+    options: &BuildOptions,
+) -> Result<bool> {
+    let library_dir = project.target_dir().join(target).join(profile_dir_name);
+    let library = find_library(&library_dir, None, CrateType::Staticlib)?;
+
+    let out_dir = project.target_dir().join(target).join("swift-bindings");
+    let hash = bindings_input_hash(&library, options)?;
+    if bindings_up_to_date(&out_dir, &hash) {
+        return Ok(false);
+    }
+    fs::recreate_dir(&out_dir)?;
+
+    if let Some(argv) = &options.bindgen_command {
+        let (program, args) = argv
+            .split_first()
+            .expect("bindgen commands are never empty");
+        let mut cmd = Command::new(program);
+        cmd.args(args)
+            .args(["generate", "--library", library.as_str()])
+            .args(["--language", "swift"])
+            .args(["--out-dir", out_dir.as_str()]);
+        cmd.successful_output()?;
+        record_bindings_hash(&out_dir, &hash)?;
+        return Ok(true);
+    }
+
+    generate_swift_bindings(SwiftBindingsOptions {
+        generate_swift_sources: true,
+        generate_headers: true,
+        generate_modulemap: true,
+        source: library,
+        out_dir: out_dir.clone(),
+        xcframework: false,
+        module_name: options.module_name.clone(),
+        modulemap_filename: options.modulemap_filename.clone(),
+        metadata_no_deps: options.metadata_no_deps,
+        link_frameworks: Vec::new(),
+        config: None,
+    })?;
+    record_bindings_hash(&out_dir, &hash)?;
+    Ok(true)
+}
+
+/// Marker file recording the input hash a bindings directory was generated
+/// from. Removed together with the directory on regeneration.
+const BINDINGS_HASH_FILE: &str = ".library-hash";
+
+/// Hash of everything the generated bindings depend on: the static library,
+/// the binding options, and the helper version (which decides the bundled
+/// uniffi-bindgen and the wrapper post-processing).
+fn bindings_input_hash(library: &Utf8Path, options: &BuildOptions) -> Result<String> {
+    let mut input =
+        std::fs::read(library).with_context(|| format!("Can't read {library}"))?;
+    input.extend_from_slice(env!("CARGO_PKG_VERSION").as_bytes());
+    for field in [
+        options.module_name.as_deref().unwrap_or_default(),
+        options.modulemap_filename.as_deref().unwrap_or_default(),
+    ] {
+        input.extend_from_slice(field.as_bytes());
+    }
+    input.push(options.metadata_no_deps as u8);
+    if let Some(argv) = &options.bindgen_command {
+        for arg in argv {
+            input.extend_from_slice(arg.as_bytes());
+        }
+    }
+    Ok(format!("{:016x}", crate::utils::fnv1a_64(&input)))
+}
+
+fn bindings_up_to_date(out_dir: &Utf8Path, hash: &str) -> bool {
+    std::fs::read_to_string(out_dir.join(BINDINGS_HASH_FILE))
+        .is_ok_and(|recorded| recorded.trim() == hash)
+}
+
+fn record_bindings_hash(out_dir: &Utf8Path, hash: &str) -> Result<()> {
+    let path = out_dir.join(BINDINGS_HASH_FILE);
+    std::fs::write(&path, format!("{hash}\n")).with_context(|| format!("Can't write {path}"))?;
+    Ok(())
+}
+
+/// Generate bindings for one crate's own static library, into
+/// `target/<triple>/swift-bindings/<internal_module_name>`. Used by the
+/// per-crate layout, where each crate ships as its own XCFramework.
+pub(crate) fn generate_crate_bindings(
+    project: &Project,
+    package: &UniffiPackage,
+    target: &str,
+    profile_dir_name: &str,
+    options: &BuildOptions,
+) -> Result<bool> {
+    let library_dir = project.target_dir().join(target).join(profile_dir_name);
+    let library = find_library(&library_dir, Some(package), CrateType::Staticlib)?;
+
+    let out_dir = project
+        .target_dir()
+        .join(target)
+        .join("swift-bindings")
+        .join(&package.internal_module_name);
+    let hash = bindings_input_hash(&library, options)?;
+    if bindings_up_to_date(&out_dir, &hash) {
+        return Ok(false);
+    }
+    fs::recreate_dir(&out_dir)?;
+
+    generate_swift_bindings(SwiftBindingsOptions {
+        generate_swift_sources: true,
+        generate_headers: true,
+        generate_modulemap: true,
+        source: library,
+        out_dir: out_dir.clone(),
+        xcframework: false,
+        module_name: Some(package.ffi_module_name()),
+        modulemap_filename: None,
+        metadata_no_deps: options.metadata_no_deps,
+        link_frameworks: Vec::new(),
+        config: None,
+    })?;
+    record_bindings_hash(&out_dir, &hash)?;
+    Ok(true)
+}
+
+/// Map a cargo profile name to the directory cargo uses under `target/`.
+pub(crate) fn profile_dir_name(profile: &str) -> &str {
+    match profile {
+        "dev" | "test" => "debug",
+        "bench" => "release",
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_dir_names() {
+        assert_eq!(profile_dir_name("dev"), "debug");
+        assert_eq!(profile_dir_name("release"), "release");
+        assert_eq!(profile_dir_name("custom"), "custom");
+    }
+
+    #[test]
+    fn artifact_messages_filter_by_package_and_crate_type() {
+        let stdout = concat!(
+            r#"{"reason":"compiler-artifact","target":{"name":"serde"},"filenames":["/t/deps/libserde.rlib"]}"#,
+            "\n",
+            r#"{"reason":"compiler-artifact","target":{"name":"other-lib"},"filenames":["/t/libother_lib.a"]}"#,
+            "\n",
+            r#"{"reason":"compiler-artifact","target":{"name":"wp-api"},"filenames":["/t/libwp_api.a","/t/libwp_api.dylib","/t/deps/libwp_api.rlib"]}"#,
+            "\n",
+            r#"{"reason":"build-finished","success":true}"#,
+            "\n",
+        );
+        let artifacts = parse_artifact_messages(stdout, "wp-api");
+        assert_eq!(
+            artifacts,
+            vec![
+                ("staticlib", Utf8PathBuf::from("/t/libwp_api.a")),
+                ("cdylib", Utf8PathBuf::from("/t/libwp_api.dylib")),
+            ]
+        );
+    }
+}
@@ -0,0 +1,76 @@
+//! Cache key derivation for CI.
+//!
+//! CI pipelines want to cache the built XCFramework, but a cache key that
+//! misses an input (a uniffi.toml edit, a helper upgrade) serves stale
+//! binaries. `cache-key` hashes everything that feeds into the build output
+//! in one place, so workflows can use it directly instead of each team
+//! hand-rolling a `hashFiles(...)` expression and forgetting half the inputs.
+
+use anyhow::{Context, Result};
+use camino::Utf8Path;
+
+use crate::project::Project;
+use crate::utils::fnv1a_64;
+use crate::xcframework::ApplePlatform;
+
+/// Print a stable hash of everything affecting the build output: the helper
+/// version, profile and platform set, `Cargo.lock`, and the full source tree
+/// of every UniFFI package (including its `uniffi.toml`).
+pub fn cache_key(platforms: &[ApplePlatform], profile: &str) -> crate::Result<()> {
+    let run = || -> Result<()> {
+        let project = Project::from_current_dir()?;
+
+        let mut input = Vec::new();
+        // The helper itself shapes the output (wrapper rewriting, module
+        // maps), so an upgrade must invalidate the cache.
+        input.extend_from_slice(env!("CARGO_PKG_VERSION").as_bytes());
+        input.extend_from_slice(profile.as_bytes());
+        for platform in platforms {
+            input.extend_from_slice(platform.name().as_bytes());
+        }
+
+        let lock_file = project.workspace_root().join("Cargo.lock");
+        input.extend_from_slice(
+            &std::fs::read(&lock_file).with_context(|| format!("Can't read {lock_file}"))?,
+        );
+
+        for package in &project.uniffi_packages {
+            hash_tree(package.manifest_dir(), package.manifest_dir(), &mut input)?;
+        }
+
+        println!("{:016x}", fnv1a_64(&input));
+        Ok(())
+    };
+    run().map_err(crate::Error::from)
+}
+
+/// Append every file under `dir` to `input` as relative path plus contents,
+/// in sorted order so the hash doesn't depend on readdir order. Hidden
+/// entries and `target` directories are skipped: they don't feed the build.
+fn hash_tree(root: &Utf8Path, dir: &Utf8Path, input: &mut Vec<u8>) -> Result<()> {
+    let mut entries: Vec<_> = dir
+        .read_dir_utf8()
+        .with_context(|| format!("Can't read {dir}"))?
+        .collect::<std::io::Result<_>>()?;
+    entries.sort_by(|a, b| a.path().cmp(b.path()));
+    for entry in entries {
+        let name = entry.file_name();
+        if name.starts_with('.') || name == "target" {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            hash_tree(root, entry.path(), input)?;
+        } else {
+            let relative = entry
+                .path()
+                .strip_prefix(root)
+                .expect("entries are always under the root");
+            input.extend_from_slice(relative.as_str().as_bytes());
+            input.extend_from_slice(
+                &std::fs::read(entry.path())
+                    .with_context(|| format!("Can't read {}", entry.path()))?,
+            );
+        }
+    }
+    Ok(())
+}
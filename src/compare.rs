@@ -0,0 +1,179 @@
+//! ABI comparison against a previously released XCFramework.
+//!
+//! Gates releases on breaking-change detection: exported symbols that
+//! disappeared and C header declarations that were removed or changed
+//! signature are reported per library, instead of consumers discovering them
+//! as link or compile errors after updating.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use camino::Utf8Path;
+
+use crate::error::Error;
+use crate::project::Project;
+use crate::utils::{fs, ExecuteCommand};
+
+/// Compare `current` (defaults to the workspace's built XCFramework) against
+/// `previous` and fail when the ABI shrank or changed.
+pub fn compare(previous: &Utf8Path, current: Option<&Utf8Path>) -> crate::Result<()> {
+    let run = || -> Result<()> {
+        let current = match current {
+            Some(path) => path.to_path_buf(),
+            None => Project::from_current_dir()?.xcframework_path(),
+        };
+        let mut report = String::new();
+
+        let previous_symbols = exported_symbols(previous)?;
+        let current_symbols = exported_symbols(&current)?;
+        for (library, symbols) in &previous_symbols {
+            let Some(current_symbols) = current_symbols.get(library) else {
+                report.push_str(&format!("Library {library} was removed\n"));
+                continue;
+            };
+            for symbol in symbols.difference(current_symbols) {
+                report.push_str(&format!("{library}: removed symbol {symbol}\n"));
+            }
+        }
+
+        let previous_decls = header_declarations(previous)?;
+        let current_decls = header_declarations(&current)?;
+        for (name, declaration) in &previous_decls {
+            match current_decls.get(name) {
+                None => report.push_str(&format!("Removed declaration: {declaration}\n")),
+                Some(current) if current != declaration => report.push_str(&format!(
+                    "Changed declaration of {name}:\n  was: {declaration}\n  now: {current}\n"
+                )),
+                Some(_) => {}
+            }
+        }
+
+        if report.is_empty() {
+            println!("No breaking ABI changes between {previous} and {current}");
+            Ok(())
+        } else {
+            bail!("Breaking ABI changes against {previous}:\n{report}")
+        }
+    };
+    run().map_err(Error::from)
+}
+
+/// Exported (defined, external) symbols per library directory name.
+fn exported_symbols(xcframework: &Utf8Path) -> Result<BTreeMap<String, BTreeSet<String>>> {
+    let mut libraries = BTreeMap::new();
+    for entry in xcframework
+        .read_dir_utf8()
+        .with_context(|| format!("Can't read {xcframework}"))?
+    {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        for library in fs::files_with_extension(entry.path(), "a")? {
+            let output = Command::new("nm")
+                .args(["-g", "--defined-only", library.as_str()])
+                .successful_output()?;
+            let stdout =
+                String::from_utf8(output.stdout).context("nm produced non-UTF-8 output")?;
+            let symbols = stdout
+                .lines()
+                .filter_map(|line| line.split_whitespace().nth(2))
+                .map(str::to_string)
+                .collect();
+            libraries.insert(entry.file_name().to_string(), symbols);
+        }
+    }
+    Ok(libraries)
+}
+
+/// All C declarations across the XCFramework's headers, keyed by declared name.
+fn header_declarations(xcframework: &Utf8Path) -> Result<BTreeMap<String, String>> {
+    let mut declarations = BTreeMap::new();
+    for entry in xcframework
+        .read_dir_utf8()
+        .with_context(|| format!("Can't read {xcframework}"))?
+    {
+        let entry = entry?;
+        let headers = entry.path().join("Headers");
+        if !headers.exists() {
+            continue;
+        }
+        for header in fs::files_with_extension(&headers, "h")? {
+            let contents =
+                std::fs::read_to_string(&header).with_context(|| format!("Can't read {header}"))?;
+            declarations.append(&mut parse_header_declarations(&contents));
+        }
+    }
+    Ok(declarations)
+}
+
+/// Extract function prototypes from a header, normalized to single-space
+/// whitespace so formatting-only changes don't register as ABI changes.
+pub(crate) fn parse_header_declarations(header: &str) -> BTreeMap<String, String> {
+    let mut declarations = BTreeMap::new();
+    let mut pending = String::new();
+    for line in header.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("//") || line.starts_with('#') {
+            pending.clear();
+            continue;
+        }
+        if !pending.is_empty() {
+            pending.push(' ');
+        }
+        pending.push_str(line);
+        if !line.ends_with(';') {
+            continue;
+        }
+        let declaration = std::mem::take(&mut pending);
+        let normalized = declaration.split_whitespace().collect::<Vec<_>>().join(" ");
+        if let Some(name) = declared_name(&normalized) {
+            declarations.insert(name, normalized);
+        }
+    }
+    declarations
+}
+
+/// The identifier a prototype declares: the token right before the first `(`.
+fn declared_name(declaration: &str) -> Option<String> {
+    let before_parens = declaration.split('(').next()?;
+    let name = before_parens.split_whitespace().last()?.trim_start_matches('*');
+    if name.is_empty() || !declaration.contains('(') {
+        return None;
+    }
+    Some(name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_normalized_prototypes() {
+        let header = "\
+// A comment
+#include <stdint.h>
+
+int32_t uniffi_wp_api_checksum_func_version(void);
+RustBuffer   ffi_wp_api_rustbuffer_alloc(
+    int32_t size,
+    RustCallStatus *status);
+";
+        let declarations = parse_header_declarations(header);
+        assert_eq!(
+            declarations.get("uniffi_wp_api_checksum_func_version").unwrap(),
+            "int32_t uniffi_wp_api_checksum_func_version(void);"
+        );
+        assert_eq!(
+            declarations.get("ffi_wp_api_rustbuffer_alloc").unwrap(),
+            "RustBuffer ffi_wp_api_rustbuffer_alloc( int32_t size, RustCallStatus *status);"
+        );
+    }
+
+    #[test]
+    fn ignores_non_prototype_lines() {
+        let declarations = parse_header_declarations("typedef int32_t MyInt;\n");
+        assert!(declarations.is_empty());
+    }
+}
@@ -0,0 +1,117 @@
+//! Rust-side coverage from the Swift test suite.
+//!
+//! Swift tests exercise the Rust code through the generated bindings, but
+//! Swift's own coverage stops at the FFI boundary, so nobody can tell which
+//! Rust functions the suite actually reaches. `coverage` rebuilds the macOS
+//! slice with `-C instrument-coverage`, drives `swift test` against it with
+//! profiling enabled, and reports the Rust coverage via
+//! llvm-profdata/llvm-cov.
+
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+
+use crate::build::{build, BuildOptions};
+use crate::events::Reporter;
+use crate::project::Project;
+use crate::utils::{fs, ExecuteCommand};
+use crate::xcframework::ApplePlatform;
+
+/// Build the macOS slice with coverage instrumentation, run the Swift test
+/// suite (optionally restricted with `filter`), and print an llvm-cov report
+/// over the UniFFI packages' Rust sources.
+pub fn coverage(rust: bool, filter: Option<&str>, reporter: &Reporter) -> crate::Result<()> {
+    let run = || -> Result<()> {
+        if !rust {
+            bail!(
+                "Only Rust-side coverage is implemented; pass --rust. For \
+                 Swift-side coverage use `swift test --enable-code-coverage`."
+            );
+        }
+        let project = Project::from_current_dir()?;
+        let profile_dir = project.tmp_dir("coverage");
+        fs::recreate_dir(&profile_dir)?;
+
+        // RUSTFLAGS is how the instrumentation reaches every crate in the
+        // build; build_uniffi_package appends the ambient value to its own.
+        let rustflags = std::env::var("RUSTFLAGS").unwrap_or_default();
+        std::env::set_var(
+            "RUSTFLAGS",
+            format!("{rustflags} -Cinstrument-coverage").trim(),
+        );
+        let built = build(
+            &[ApplePlatform::MacOS],
+            "dev",
+            &BuildOptions::default(),
+            reporter,
+        );
+        std::env::set_var("RUSTFLAGS", rustflags);
+        built?;
+
+        let mut cmd = Command::new("swift");
+        cmd.arg("test");
+        if let Some(filter) = filter {
+            cmd.args(["--filter", filter]);
+        }
+        // %p keeps parallel test processes from clobbering each other's data.
+        cmd.env(
+            "LLVM_PROFILE_FILE",
+            profile_dir.join("swift-tests-%p.profraw"),
+        );
+        cmd.successful_output().context("Test run failed")?;
+
+        let profraws = fs::files_with_extension(&profile_dir, "profraw")?;
+        if profraws.is_empty() {
+            bail!(
+                "The test run produced no .profraw files; was the Rust code \
+                 actually rebuilt with instrumentation?"
+            );
+        }
+        let profdata = profile_dir.join("coverage.profdata");
+        let mut cmd = crate::utils::xcrun_tool("llvm-profdata");
+        cmd.args(["merge", "-sparse"]);
+        for profraw in &profraws {
+            cmd.arg(profraw);
+        }
+        cmd.args(["-o", profdata.as_str()]);
+        cmd.successful_output()?;
+
+        // The coverage mapping lives in the test binary that linked the
+        // instrumented static library.
+        let test_binary = find_test_binary()?;
+        let mut cmd = crate::utils::xcrun_tool("llvm-cov");
+        cmd.args([
+            "report",
+            test_binary.as_str(),
+            &format!("-instr-profile={profdata}"),
+        ]);
+        // Restrict the report to the UniFFI packages' own sources; the
+        // scaffolding and dependencies would drown them out.
+        for package in &project.uniffi_packages {
+            cmd.arg(package.manifest_dir());
+        }
+        let output = cmd.successful_output()?;
+        print!("{}", String::from_utf8_lossy(&output.stdout));
+        Ok(())
+    };
+    run().map_err(crate::Error::from)
+}
+
+/// The XCTest executable `swift test` just built, which carries the Rust
+/// coverage mapping.
+fn find_test_binary() -> Result<camino::Utf8PathBuf> {
+    let output = Command::new("swift")
+        .args(["build", "--show-bin-path"])
+        .successful_output()?;
+    let bin_dir = camino::Utf8PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    for entry in fs::subdirs(&bin_dir)? {
+        let Some(bundle_name) = entry.file_name().and_then(|n| n.strip_suffix(".xctest")) else {
+            continue;
+        };
+        let binary = entry.join("Contents/MacOS").join(bundle_name);
+        if binary.exists() {
+            return Ok(binary);
+        }
+    }
+    bail!("No .xctest bundle found in {bin_dir}")
+}
@@ -0,0 +1,135 @@
+//! Deployment target inference from a consumer Xcode project.
+//!
+//! Host apps already declare their minimum OS versions as Xcode build
+//! settings (`IPHONEOS_DEPLOYMENT_TARGET` and friends). Instead of
+//! hard-coding the SDK's minimums in two places, the helper can parse them
+//! out of the app's `.xcodeproj` (or an xcconfig file) and apply them to the
+//! Rust builds and the generated `Package.swift`.
+
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Context, Result};
+use camino::Utf8Path;
+
+/// The build settings that carry minimum OS versions, with the SPM platform
+/// constructor each one maps to.
+const SETTINGS: [(&str, &str); 4] = [
+    ("IPHONEOS_DEPLOYMENT_TARGET", "iOS"),
+    ("MACOSX_DEPLOYMENT_TARGET", "macOS"),
+    ("TVOS_DEPLOYMENT_TARGET", "tvOS"),
+    ("WATCHOS_DEPLOYMENT_TARGET", "watchOS"),
+];
+
+/// Minimum OS versions read from a consumer project, keyed by build setting
+/// name.
+pub(crate) struct DeploymentTargets {
+    versions: BTreeMap<&'static str, String>,
+}
+
+impl DeploymentTargets {
+    /// Read deployment targets from `path`: an `.xcodeproj` bundle (its
+    /// `project.pbxproj`) or an xcconfig-style file.
+    pub(crate) fn from_path(path: &Utf8Path) -> Result<Self> {
+        let file = if path.extension() == Some("xcodeproj") {
+            path.join("project.pbxproj")
+        } else {
+            path.to_path_buf()
+        };
+        let contents =
+            std::fs::read_to_string(&file).with_context(|| format!("Can't read {file}"))?;
+        let targets = Self::parse(&contents);
+        if targets.versions.is_empty() {
+            bail!("No deployment target settings found in {file}");
+        }
+        Ok(targets)
+    }
+
+    /// Collect `SETTING = version` assignments from pbxproj or xcconfig
+    /// content. When a setting appears more than once (e.g. per build
+    /// configuration), the lowest version wins: the SDK must support the
+    /// least demanding configuration of the app.
+    fn parse(contents: &str) -> Self {
+        let mut versions: BTreeMap<&'static str, String> = BTreeMap::new();
+        for line in contents.lines() {
+            let line = line.trim();
+            let Some((setting, _)) = SETTINGS
+                .iter()
+                .find(|(setting, _)| line.starts_with(setting))
+            else {
+                continue;
+            };
+            let Some(value) = line[setting.len()..].trim_start().strip_prefix('=') else {
+                continue;
+            };
+            let value = value.trim().trim_end_matches(';').trim_matches('"').trim();
+            if value.is_empty() {
+                continue;
+            }
+            versions
+                .entry(setting)
+                .and_modify(|existing| {
+                    if version_less_than(value, existing) {
+                        *existing = value.to_string();
+                    }
+                })
+                .or_insert_with(|| value.to_string());
+        }
+        Self { versions }
+    }
+
+    /// The configured minimum version for one build setting, e.g.
+    /// `IPHONEOS_DEPLOYMENT_TARGET`.
+    pub(crate) fn version(&self, setting: &str) -> Option<&str> {
+        self.versions.get(setting).map(String::as_str)
+    }
+
+    /// `NAME=version` pairs for the environment of cargo invocations, where
+    /// cc/rustc pick the minimums up directly.
+    pub(crate) fn env_vars(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        self.versions.iter().map(|(name, version)| (*name, version.as_str()))
+    }
+
+    /// SPM `platforms:` entries, e.g. `.iOS("15.0")`.
+    pub(crate) fn spm_platforms(&self) -> Vec<String> {
+        SETTINGS
+            .iter()
+            .filter_map(|(setting, platform)| {
+                self.versions
+                    .get(setting)
+                    .map(|version| format!(".{platform}(\"{version}\")"))
+            })
+            .collect()
+    }
+}
+
+/// Compare dotted version strings numerically, component by component.
+pub(crate) fn version_less_than(a: &str, b: &str) -> bool {
+    let components = |v: &str| -> Vec<u32> {
+        v.split('.').map(|c| c.parse().unwrap_or(0)).collect()
+    };
+    components(a) < components(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_lowest_version_per_setting() {
+        let contents = "\
+            IPHONEOS_DEPLOYMENT_TARGET = 16.0;\n\
+            IPHONEOS_DEPLOYMENT_TARGET = 15.2;\n\
+            MACOSX_DEPLOYMENT_TARGET = \"12.0\"\n";
+        let targets = DeploymentTargets::parse(contents);
+        assert_eq!(
+            targets.spm_platforms(),
+            vec![".iOS(\"15.2\")", ".macOS(\"12.0\")"]
+        );
+    }
+
+    #[test]
+    fn version_comparison_is_numeric() {
+        assert!(version_less_than("9.3", "15.0"));
+        assert!(!version_less_than("15.0", "9.3"));
+    }
+}
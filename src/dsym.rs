@@ -0,0 +1,66 @@
+//! Debug symbol extraction and upload.
+//!
+//! Rust frames are lost in production crash reports unless the dSYMs for the
+//! built slices reach the crash reporting service. After packaging, we can
+//! extract dSYMs from each library in the XCFramework and hand them to a
+//! configurable uploader command (`sentry-cli upload-dif`, `datadog-ci dsyms
+//! upload`, Crashlytics' `upload-symbols`, …). Credentials are whatever the
+//! uploader reads from its environment; the helper doesn't handle them.
+
+use std::process::Command;
+
+use anyhow::Result;
+use camino::{Utf8Path, Utf8PathBuf};
+
+use crate::project::Project;
+use crate::utils::{fs, ExecuteCommand};
+
+/// Environment variable consulted when no uploader is given on the command
+/// line. Split on whitespace into an argv.
+pub const DSYM_UPLOADER_ENV: &str = "UNIFFI_SWIFT_HELPER_DSYM_UPLOADER";
+
+/// Run `dsymutil` over every library in the XCFramework, writing one `.dSYM`
+/// bundle per library into `target/<ffi_module_name>/dsyms`.
+pub(crate) fn extract_dsyms(project: &Project, xcframework: &Utf8Path) -> Result<Vec<Utf8PathBuf>> {
+    let out_dir = project.dsyms_dir();
+    // Bundles are prefixed with the framework name so the per-crate layout's
+    // frameworks don't overwrite each other's dSYMs.
+    let framework_name = xcframework.file_stem().unwrap_or("xcframework");
+    fs::recreate_dir(&out_dir.join(framework_name))?;
+
+    let mut dsyms = Vec::new();
+    for entry in xcframework.read_dir_utf8()? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        for library in fs::files_with_extension(entry.path(), "a")? {
+            let dsym = out_dir
+                .join(framework_name)
+                .join(format!("{}.dSYM", entry.file_name()));
+            crate::utils::xcrun_tool("dsymutil")
+                .args([library.as_str(), "-o", dsym.as_str()])
+                .successful_output()?;
+            dsyms.push(dsym);
+        }
+    }
+    Ok(dsyms)
+}
+
+/// Invoke `uploader` once per dSYM, with the bundle path appended as the last
+/// argument. The subprocess inherits our environment, so service credentials
+/// (e.g. `SENTRY_AUTH_TOKEN`, `DATADOG_API_KEY`) work as they do for manual
+/// invocations.
+pub(crate) fn upload_dsyms(uploader: &[String], dsyms: &[Utf8PathBuf]) -> Result<()> {
+    let (program, args) = uploader
+        .split_first()
+        .expect("uploader command is never empty");
+    for dsym in dsyms {
+        Command::new(program)
+            .args(args)
+            .arg(dsym)
+            .successful_output()?;
+        println!("Uploaded {dsym}");
+    }
+    Ok(())
+}
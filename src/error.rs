@@ -0,0 +1,71 @@
+use camino::Utf8PathBuf;
+
+/// The failure causes a programmatic user can match on.
+///
+/// Internally the crate still uses `anyhow` for context-rich errors; anything
+/// without a dedicated variant surfaces as [`Error::Other`]. Typed variants
+/// created deep inside the crate travel through `anyhow` and are recovered by
+/// the [`From<anyhow::Error>`] impl at the public API boundary.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// A tier-3 target was requested but the required nightly toolchain
+    /// (with rust-src) is not installed.
+    #[error("A nightly Rust toolchain with the rust-src component is required to build for {target}")]
+    MissingToolchain { target: String },
+
+    /// A subprocess exited with a non-zero status.
+    #[error("Command {cmd} exited with an error:\n{stderr}")]
+    CommandFailed { cmd: String, stderr: String },
+
+    /// A package's `uniffi.toml` is missing a key the helper needs.
+    #[error("Missing required key `{key}` in {package}'s uniffi.toml")]
+    MissingUniffiTomlKey { package: String, key: String },
+
+    /// The helper was invoked from somewhere other than the workspace root.
+    #[error("This command must run from the workspace root ({workspace_root})")]
+    NotWorkspaceRoot { workspace_root: Utf8PathBuf },
+
+    /// The workspace has no packages that depend on uniffi and carry a
+    /// `uniffi.toml`.
+    #[error("No UniFFI packages found in the workspace")]
+    NoUniffiPackages,
+
+    #[error(transparent)]
+    Other(anyhow::Error),
+}
+
+impl From<anyhow::Error> for Error {
+    fn from(error: anyhow::Error) -> Self {
+        error.downcast::<Error>().unwrap_or_else(Error::Other)
+    }
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_errors_survive_an_anyhow_round_trip() {
+        let typed = Error::MissingUniffiTomlKey {
+            package: "wp_api".to_string(),
+            key: "ffi_module_name".to_string(),
+        };
+        let through_anyhow: anyhow::Error = typed.into();
+        match Error::from(through_anyhow) {
+            Error::MissingUniffiTomlKey { package, key } => {
+                assert_eq!(package, "wp_api");
+                assert_eq!(key, "ffi_module_name");
+            }
+            other => panic!("expected MissingUniffiTomlKey, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn untyped_errors_become_other() {
+        let error = Error::from(anyhow::anyhow!("something else"));
+        assert!(matches!(error, Error::Other(_)));
+    }
+}
@@ -0,0 +1,91 @@
+//! Build progress reporting.
+//!
+//! The library emits [`BuildEvent`]s through a [`Reporter`] while a build is
+//! running, so callers can render progress however they like (the CLI draws
+//! progress bars). Long-running subprocess work would otherwise be silent.
+
+use std::fmt;
+
+/// A coarse phase of the build pipeline.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BuildPhase {
+    /// Compiling the Rust libraries, one cargo invocation per package/target.
+    RustBuild,
+    /// Generating Swift bindings, headers, and module maps per target.
+    Bindings,
+    /// Merging slices and assembling the XCFramework.
+    Package,
+    /// Post-processing the generated Swift wrapper sources.
+    Wrappers,
+}
+
+impl fmt::Display for BuildPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::RustBuild => "Building Rust libraries",
+            Self::Bindings => "Generating Swift bindings",
+            Self::Package => "Packaging XCFramework",
+            Self::Wrappers => "Updating Swift wrappers",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Something that happened during a build.
+#[derive(Clone, Debug)]
+pub enum BuildEvent {
+    /// A phase began; `steps` is how many `StepFinished` events to expect.
+    PhaseStarted { phase: BuildPhase, steps: usize },
+    /// One unit of work within a phase completed, e.g. a target was built.
+    StepFinished { phase: BuildPhase, step: String },
+    /// A phase completed.
+    PhaseFinished { phase: BuildPhase },
+    /// An artifact was copied during packaging.
+    BytesCopied { bytes: u64 },
+}
+
+type Callback = Box<dyn Fn(&BuildEvent) + Send + Sync>;
+
+/// Receives [`BuildEvent`]s and forwards them to an optional callback.
+#[derive(Default)]
+pub struct Reporter {
+    callback: Option<Callback>,
+}
+
+impl Reporter {
+    pub fn new(callback: impl Fn(&BuildEvent) + Send + Sync + 'static) -> Self {
+        Self {
+            callback: Some(Box::new(callback)),
+        }
+    }
+
+    /// A reporter that discards all events.
+    pub fn silent() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn emit(&self, event: BuildEvent) {
+        if let Some(callback) = &self.callback {
+            callback(&event);
+        }
+    }
+
+    pub(crate) fn phase_started(&self, phase: BuildPhase, steps: usize) {
+        self.emit(BuildEvent::PhaseStarted { phase, steps });
+    }
+
+    pub(crate) fn step_finished(&self, phase: BuildPhase, step: impl Into<String>) {
+        self.emit(BuildEvent::StepFinished {
+            phase,
+            step: step.into(),
+        });
+    }
+
+    pub(crate) fn phase_finished(&self, phase: BuildPhase) {
+        self.emit(BuildEvent::PhaseFinished { phase });
+    }
+
+    pub(crate) fn bytes_copied(&self, bytes: u64) {
+        self.emit(BuildEvent::BytesCopied { bytes });
+    }
+}
@@ -0,0 +1,79 @@
+//! Example app generation.
+//!
+//! SDK evaluators want something runnable before they read any docs. The
+//! `example` subcommand emits a small SwiftUI executable package next to the
+//! generated `Package.swift` that depends on every public module, so
+//! `swift run` (on macOS) demonstrates the bindings end to end.
+
+use anyhow::{bail, Context, Result};
+use rinja::Template;
+
+use crate::project::Project;
+
+/// Generate a runnable SwiftUI example package under `Example/` at the
+/// workspace root, depending on the generated SDK package. Refuses to touch
+/// an existing `Example` directory unless `force` is set, since evaluators
+/// are expected to edit the scaffold.
+pub fn generate_example(force: bool) -> crate::Result<()> {
+    let run = || -> Result<()> {
+        let project = Project::from_current_dir()?;
+        let example_dir = project.workspace_root().join("Example");
+        if example_dir.exists() {
+            if !force {
+                bail!("{example_dir} already exists. Pass --force to overwrite it.");
+            }
+            std::fs::remove_dir_all(&example_dir)
+                .with_context(|| format!("Can't remove {example_dir}"))?;
+        }
+
+        let products: Vec<String> = project
+            .uniffi_packages
+            .iter()
+            .map(|package| package.public_module_name.clone())
+            .collect();
+        let name = project.ffi_module_name.as_str();
+
+        let manifest = ExamplePackage {
+            tools_version: &project.swift_tools_version,
+            name,
+            products: &products,
+        }
+        .render()
+        .context("Can't render the example Package.swift")?;
+        let app = ExampleApp {
+            name,
+            products: &products,
+        }
+        .render()
+        .context("Can't render the example app source")?;
+
+        let sources_dir = example_dir.join(format!("Sources/{name}Example"));
+        std::fs::create_dir_all(&sources_dir)
+            .with_context(|| format!("Can't create {sources_dir}"))?;
+        let manifest_path = example_dir.join("Package.swift");
+        std::fs::write(&manifest_path, manifest)
+            .with_context(|| format!("Can't write {manifest_path}"))?;
+        let app_path = sources_dir.join(format!("{name}ExampleApp.swift"));
+        std::fs::write(&app_path, app).with_context(|| format!("Can't write {app_path}"))?;
+
+        println!("Generated example app in {example_dir}");
+        println!("Run it with: cd {example_dir} && swift run");
+        Ok(())
+    };
+    run().map_err(crate::Error::from)
+}
+
+#[derive(Template)]
+#[template(path = "ExamplePackage.swift", escape = "none")]
+struct ExamplePackage<'a> {
+    tools_version: &'a str,
+    name: &'a str,
+    products: &'a [String],
+}
+
+#[derive(Template)]
+#[template(path = "ExampleApp.swift", escape = "none")]
+struct ExampleApp<'a> {
+    name: &'a str,
+    products: &'a [String],
+}
@@ -0,0 +1,86 @@
+//! FFI surface fingerprinting.
+//!
+//! A rebuilt XCFramework differs byte-for-byte on almost every commit, but
+//! the FFI *surface* — the generated C headers and module map — only changes
+//! when the exported API does. `fingerprint` hashes exactly that surface and
+//! records it next to the artifacts, so caching layers and compatibility
+//! checks can compare two builds without diffing binaries.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{bail, Context, Result};
+use camino::Utf8Path;
+
+use crate::project::Project;
+use crate::utils::{fnv1a_64, fs};
+
+/// Compute a stable hash over the generated C headers and module maps in the
+/// built XCFramework, print it, and record it (with the per-file hashes) in
+/// `<output_root>/<ffi_module_name>/abi-fingerprint.json`.
+///
+/// Per-slice copies of the same header are deduplicated, so the fingerprint
+/// depends only on the API surface, not on which platforms were built.
+pub fn fingerprint() -> crate::Result<()> {
+    let run = || -> Result<()> {
+        let project = Project::from_current_dir()?;
+        let xcframework = project.xcframework_path();
+        if !xcframework.exists() {
+            bail!("No XCFramework at {xcframework}. Run `uniffi-swift-helper build` first.");
+        }
+
+        let files = header_hashes(&xcframework)?;
+        if files.is_empty() {
+            bail!("No headers found in {xcframework}");
+        }
+
+        let mut input = Vec::new();
+        for (name, hashes) in &files {
+            input.extend_from_slice(name.as_bytes());
+            for hash in hashes {
+                input.extend_from_slice(hash.as_bytes());
+            }
+        }
+        let fingerprint = format!("{:016x}", fnv1a_64(&input));
+
+        let manifest = serde_json::json!({
+            "ffi_module_name": project.ffi_module_name,
+            "fingerprint": fingerprint,
+            "files": files,
+        });
+        let manifest_dir = project.output_root().join(&project.ffi_module_name);
+        std::fs::create_dir_all(&manifest_dir)
+            .with_context(|| format!("Can't create {manifest_dir}"))?;
+        let manifest_path = manifest_dir.join("abi-fingerprint.json");
+        std::fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)
+            .with_context(|| format!("Can't write {manifest_path}"))?;
+
+        println!("{fingerprint}");
+        println!("Recorded in {manifest_path}");
+        Ok(())
+    };
+    run().map_err(crate::Error::from)
+}
+
+/// The content hashes of every header and module map under the XCFramework's
+/// `Headers` directories, keyed by file name. A file whose contents diverge
+/// between slices (which would itself be a bug) contributes every variant.
+fn header_hashes(xcframework: &Utf8Path) -> Result<BTreeMap<String, BTreeSet<String>>> {
+    let mut files: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for library_dir in fs::subdirs(xcframework)? {
+        let headers = library_dir.join("Headers");
+        if !headers.exists() {
+            continue;
+        }
+        for extension in ["h", "modulemap"] {
+            for header in fs::files_with_extension(&headers, extension)? {
+                let contents =
+                    std::fs::read(&header).with_context(|| format!("Can't read {header}"))?;
+                files
+                    .entry(header.file_name().unwrap_or_default().to_string())
+                    .or_default()
+                    .insert(format!("{:016x}", fnv1a_64(&contents)));
+            }
+        }
+    }
+    Ok(files)
+}
@@ -0,0 +1,183 @@
+//! Single-platform framework bundles.
+//!
+//! Some consumers embed a plain `.framework` for one platform instead of an
+//! XCFramework — typically mac apps that link the FFI directly into an
+//! existing Xcode project. `build-framework` compiles the Rust libraries for
+//! that platform's device triples and lays out a framework bundle: universal
+//! binary, `Headers`, and `Modules/module.modulemap`. The binary is the
+//! staticlib by default, or the cdylib with `--dynamic` for apps that want
+//! to load the FFI at runtime.
+
+
+use anyhow::{bail, Context, Result};
+use camino::Utf8PathBuf;
+
+use crate::build::{build_uniffi_package, generate_bindings, profile_dir_name, BuildOptions, CrateType};
+use crate::events::{BuildPhase, Reporter};
+use crate::project::Project;
+use crate::utils::{fs, ExecuteCommand};
+use crate::xcframework::{
+    collect_groups, lipo_command, merge_extra_archives, ApplePlatform, LibraryGroupId, Slice,
+};
+
+/// Build a `.framework` bundle for one platform at
+/// `target/<FfiModuleName>.framework`, static by default or dynamic — from
+/// the crates' cdylib output — with `dynamic`.
+///
+/// Only the platform's device slices go into the binary: a single framework
+/// can't mix device and simulator code (that's what XCFrameworks are for).
+pub fn build_framework(
+    platform: ApplePlatform,
+    profile: &str,
+    dynamic: bool,
+    reporter: &Reporter,
+) -> crate::Result<()> {
+    let run = || -> Result<()> {
+        let project = Project::from_current_dir()?;
+        let options = BuildOptions::default();
+        let profile_dir = profile_dir_name(profile);
+        let targets: Vec<&str> = platform
+            .target_triples()
+            .into_iter()
+            .filter(|target| {
+                LibraryGroupId::from_target(target).is_ok_and(|id| !id.simulator)
+            })
+            .collect();
+        if targets.is_empty() {
+            bail!("{} has no device targets to put into a framework", platform.name());
+        }
+
+        reporter.phase_started(BuildPhase::RustBuild, targets.len() * project.uniffi_packages.len());
+        for target in &targets {
+            for package in &project.uniffi_packages {
+                build_uniffi_package(&project, package, target, platform, profile, &options, None)?;
+                reporter.step_finished(
+                    BuildPhase::RustBuild,
+                    format!("{} ({target})", package.package.name),
+                );
+            }
+        }
+        reporter.phase_finished(BuildPhase::RustBuild);
+
+        reporter.phase_started(BuildPhase::Bindings, 1);
+        generate_bindings(&project, targets[0], profile_dir, &options)?;
+        reporter.step_finished(BuildPhase::Bindings, targets[0]);
+        reporter.phase_finished(BuildPhase::Bindings);
+
+        reporter.phase_started(BuildPhase::Package, 1);
+        let crate_type = if dynamic {
+            CrateType::Cdylib
+        } else {
+            CrateType::Staticlib
+        };
+        let groups = collect_groups(&targets, |target| {
+            let slice = Slice::create(&project, target, profile_dir, crate_type)?;
+            if dynamic {
+                // Extra archives are static-only; linking them into the
+                // cdylib is cargo's job via the crate's build script.
+                return Ok(slice);
+            }
+            merge_extra_archives(&project, slice)
+        })?;
+        let group = groups
+            .values()
+            .next()
+            .expect("device targets always form one group");
+
+        let name = &project.ffi_module_name;
+        let framework = project.framework_path(name);
+        let _lock = crate::utils::WorkspaceLock::acquire(project.target_dir())?;
+        fs::recreate_dir(&framework)?;
+
+        let binary = framework.join(name);
+        match group.slices.as_slice() {
+            [slice] => {
+                fs::clone_or_copy(&slice.library_path, &binary)?;
+            }
+            slices => {
+                let mut cmd = lipo_command();
+                cmd.arg("-create");
+                for slice in slices {
+                    cmd.arg(&slice.library_path);
+                }
+                cmd.args(["-output", binary.as_str()]);
+                cmd.successful_output()?;
+            }
+        }
+
+        if dynamic {
+            // The binary must advertise its in-bundle location, or the app's
+            // loader won't find it after embedding.
+            crate::utils::xcrun_tool("install_name_tool")
+                .args([
+                    "-id",
+                    &format!("@rpath/{name}.framework/{name}"),
+                    binary.as_str(),
+                ])
+                .successful_output()?;
+        }
+
+        let headers = framework.join("Headers");
+        fs::recreate_dir(&headers)?;
+        let bindings_dir = project.target_dir().join(targets[0]).join("swift-bindings");
+        let mut header_files = Vec::new();
+        for header in fs::files_with_extension(&bindings_dir, "h")? {
+            let file_name = header.file_name().unwrap().to_string();
+            let bytes = std::fs::copy(&header, headers.join(&file_name))
+                .with_context(|| format!("Can't copy {header}"))?;
+            reporter.bytes_copied(bytes);
+            header_files.push(file_name);
+        }
+
+        write_framework_modulemap(&framework, name, &header_files)?;
+        write_framework_info_plist(&framework, name)?;
+        reporter.step_finished(BuildPhase::Package, framework.to_string());
+        reporter.phase_finished(BuildPhase::Package);
+        println!("Created {framework}");
+        Ok(())
+    };
+    run().map_err(crate::Error::from)
+}
+
+/// Write `Modules/module.modulemap` as a framework module, so clang resolves
+/// the headers through the bundle without extra search paths.
+fn write_framework_modulemap(
+    framework: &Utf8PathBuf,
+    name: &str,
+    header_files: &[String],
+) -> Result<()> {
+    let modules = framework.join("Modules");
+    fs::recreate_dir(&modules)?;
+    let mut contents = format!("framework module {name} {{\n");
+    for header in header_files {
+        contents.push_str(&format!("    header \"{header}\"\n"));
+    }
+    contents.push_str("    export *\n}\n");
+    let path = modules.join("module.modulemap");
+    std::fs::write(&path, contents).with_context(|| format!("Can't write {path}"))?;
+    Ok(())
+}
+
+/// Write the minimal `Info.plist` a framework bundle needs to be embedded.
+fn write_framework_info_plist(framework: &Utf8PathBuf, name: &str) -> Result<()> {
+    let contents = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>CFBundleExecutable</key>
+	<string>{name}</string>
+	<key>CFBundleIdentifier</key>
+	<string>org.uniffi.{name}</string>
+	<key>CFBundleName</key>
+	<string>{name}</string>
+	<key>CFBundlePackageType</key>
+	<string>FMWK</string>
+</dict>
+</plist>
+"#
+    );
+    let path = framework.join("Info.plist");
+    std::fs::write(&path, contents).with_context(|| format!("Can't write {path}"))?;
+    Ok(())
+}
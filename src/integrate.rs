@@ -0,0 +1,221 @@
+//! Consumer manifest integration.
+//!
+//! Every release, app teams edit their own `Package.swift` to point at the
+//! new SDK version — an error-prone chore when the dependency entry and the
+//! per-target product references must stay in sync. `integrate` rewrites a
+//! consumer manifest in place: the SDK's `.package(...)` entry is replaced
+//! (or inserted) with the requested local path or released URL, and existing
+//! `.product(...)` references to our modules are repointed at the new
+//! package identity.
+
+use anyhow::{bail, Context, Result};
+use camino::Utf8Path;
+
+use crate::project::Project;
+
+/// Wire the SDK into the consumer manifest at `manifest_path`, as a local
+/// path dependency (`path`) or a released version (`url` plus `version`).
+/// Exactly one of the two forms must be given.
+pub fn integrate(
+    manifest_path: &Utf8Path,
+    path: Option<&Utf8Path>,
+    url: Option<&str>,
+    version: Option<&str>,
+) -> crate::Result<()> {
+    let run = || -> Result<()> {
+        let (identity, entry) = match (path, url) {
+            (Some(path), None) => (package_identity(path.as_str()), format!(".package(path: \"{path}\")")),
+            (None, Some(url)) => {
+                let Some(version) = version else {
+                    bail!("--url needs --version to pin the release");
+                };
+                (
+                    package_identity(url),
+                    format!(".package(url: \"{url}\", from: \"{version}\")"),
+                )
+            }
+            _ => bail!("Pass exactly one of --path or --url"),
+        };
+
+        let project = Project::from_current_dir()?;
+        let products: Vec<&str> = project
+            .uniffi_packages
+            .iter()
+            .map(|package| package.public_module_name.as_str())
+            .collect();
+
+        let manifest = std::fs::read_to_string(manifest_path)
+            .with_context(|| format!("Can't read {manifest_path}"))?;
+        let updated = replace_or_insert_dependency(&manifest, &identity, &entry)?;
+        let (updated, repointed) = repoint_product_references(&updated, &products, &identity);
+
+        if updated == manifest {
+            println!("{manifest_path} already up to date");
+            return Ok(());
+        }
+        std::fs::write(manifest_path, &updated)
+            .with_context(|| format!("Can't write {manifest_path}"))?;
+        println!("Updated the {identity} dependency in {manifest_path}");
+        if repointed == 0 {
+            println!(
+                "No target references a product yet. Add e.g. \
+                 .product(name: \"{}\", package: \"{identity}\") to a target's dependencies.",
+                products.first().copied().unwrap_or("...")
+            );
+        }
+        Ok(())
+    };
+    run().map_err(crate::Error::from)
+}
+
+/// The package identity SPM derives from a dependency location: the last
+/// path component, minus a `.git` suffix.
+fn package_identity(location: &str) -> String {
+    let name = location
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or(location);
+    name.strip_suffix(".git").unwrap_or(name).to_string()
+}
+
+/// Replace the existing `.package(...)` entry mentioning `identity` with
+/// `entry`, or insert `entry` at the start of the `dependencies` array when
+/// there is none yet.
+fn replace_or_insert_dependency(manifest: &str, identity: &str, entry: &str) -> Result<String> {
+    // An existing entry is any `.package(` whose balanced argument list
+    // mentions the identity, so both url and path forms are found.
+    let mut search_from = 0;
+    while let Some(offset) = manifest[search_from..].find(".package(") {
+        let start = search_from + offset;
+        let open = start + ".package".len();
+        let Some(close) = matching_paren(manifest, open) else {
+            break;
+        };
+        if manifest[open..close].contains(identity) {
+            return Ok(format!(
+                "{}{entry}{}",
+                &manifest[..start],
+                &manifest[close + 1..]
+            ));
+        }
+        search_from = close + 1;
+    }
+
+    let Some(offset) = manifest.find("dependencies: [") else {
+        bail!(
+            "The manifest has no dependencies array; add `dependencies: []` \
+             to the Package initializer first"
+        );
+    };
+    let insert_at = offset + "dependencies: [".len();
+    // Match the indentation of the line the array opens on, one level deeper.
+    let line_start = manifest[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let indent: String = manifest[line_start..offset]
+        .chars()
+        .take_while(|c| c.is_whitespace())
+        .collect();
+    Ok(format!(
+        "{}\n{indent}    {entry},{}",
+        &manifest[..insert_at],
+        &manifest[insert_at..]
+    ))
+}
+
+/// The index of the `)` closing the `(` at `open`, honoring nesting. String
+/// literals in manifests don't contain parens in practice, so plain counting
+/// suffices.
+fn matching_paren(text: &str, open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, c) in text[open..].char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(open + i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Point every `.product(name: <one of ours>, package: ...)` reference at
+/// `identity`, returning the rewritten manifest and how many references were
+/// found.
+fn repoint_product_references(
+    manifest: &str,
+    products: &[&str],
+    identity: &str,
+) -> (String, usize) {
+    let mut result = String::with_capacity(manifest.len());
+    let mut found = 0;
+    for line in manifest.lines() {
+        let ours = products
+            .iter()
+            .any(|product| line.contains(&format!(".product(name: \"{product}\"")));
+        if ours {
+            found += 1;
+            if let Some(start) = line.find("package: \"") {
+                let prefix = &line[..start + "package: \"".len()];
+                let suffix = &line[start + "package: \"".len()..];
+                let end = suffix.find('"').unwrap_or(suffix.len());
+                result.push_str(prefix);
+                result.push_str(identity);
+                result.push_str(&suffix[end..]);
+                result.push('\n');
+                continue;
+            }
+        }
+        result.push_str(line);
+        result.push('\n');
+    }
+    (result, found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dependency_is_replaced_or_inserted() {
+        let manifest = "\
+let package = Package(
+    name: \"App\",
+    dependencies: [
+        .package(url: \"https://example.com/sdk.git\", from: \"1.0.0\"),
+    ],
+)
+";
+        let updated = replace_or_insert_dependency(
+            manifest,
+            "sdk",
+            ".package(url: \"https://example.com/sdk.git\", from: \"2.0.0\")",
+        )
+        .unwrap();
+        assert!(updated.contains("from: \"2.0.0\""));
+        assert!(!updated.contains("from: \"1.0.0\""));
+
+        let inserted =
+            replace_or_insert_dependency(manifest, "other-sdk", ".package(path: \"../other-sdk\")")
+                .unwrap();
+        assert!(inserted.contains(".package(path: \"../other-sdk\"),"));
+        assert!(inserted.contains("from: \"1.0.0\""));
+    }
+
+    #[test]
+    fn product_references_are_repointed() {
+        let manifest = "        .product(name: \"Api\", package: \"old-sdk\"),\n";
+        let (updated, found) = repoint_product_references(manifest, &["Api"], "sdk");
+        assert_eq!(found, 1);
+        assert_eq!(updated, "        .product(name: \"Api\", package: \"sdk\"),\n");
+    }
+
+    #[test]
+    fn package_identity_strips_git_suffix() {
+        assert_eq!(package_identity("https://example.com/a/sdk.git"), "sdk");
+        assert_eq!(package_identity("../sdk"), "sdk");
+    }
+}
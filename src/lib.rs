@@ -0,0 +1,77 @@
+//! Helper library for packaging UniFFI-based Rust crates as Swift packages.
+//!
+//! The workflow has two halves:
+//!
+//! 1. [`build`] compiles every UniFFI crate in the workspace for the requested
+//!    Apple platforms, generates the Swift bindings, and assembles the merged
+//!    static libraries into an XCFramework.
+//! 2. [`generate_swift_package`] writes a `Package.swift` at the workspace root
+//!    that wires the XCFramework, the generated bindings targets, and the
+//!    hand-written Swift wrapper sources together.
+
+mod artifact_bundle;
+mod bazel;
+mod bench;
+mod bloat;
+mod build;
+mod cache_key;
+mod compare;
+mod coverage;
+mod deployment;
+mod dsym;
+mod error;
+mod events;
+mod example;
+mod fingerprint;
+mod framework;
+mod integrate;
+mod lint;
+mod minos;
+mod notarize;
+mod project;
+mod release;
+mod reproducible;
+mod spm;
+mod summary;
+mod symbols;
+mod tuist;
+mod utils;
+mod watch;
+mod wrapper_framework;
+mod xcode;
+mod xcframework;
+
+pub use artifact_bundle::package_artifact_bundle;
+pub use bazel::generate_bazel;
+pub use bench::bench;
+pub use bloat::{bloat, CrateSize, SliceReport};
+pub use build::{build, regenerate_bindings, BuildOptions, BuildStage};
+pub use cache_key::cache_key;
+pub use compare::compare;
+pub use coverage::coverage;
+pub use dsym::DSYM_UPLOADER_ENV;
+pub use error::{Error, Result};
+pub use events::{BuildEvent, BuildPhase, Reporter};
+pub use example::generate_example;
+pub use fingerprint::fingerprint;
+pub use framework::build_framework;
+pub use integrate::integrate;
+pub use lint::lint;
+pub use minos::verify_min_os;
+pub use notarize::notarize;
+pub use project::show_config;
+pub use release::release;
+pub use reproducible::verify_reproducible;
+pub use spm::{
+    generate_swift_package, generate_test_scaffolds, vendor_swift_sources, verify_swift_package,
+    GeneratePackageOptions,
+};
+pub use tuist::generate_tuist;
+pub use utils::{
+    set_cargo_frozen, set_cargo_locked, set_command_timeout, set_dry_run, set_log_file,
+    set_offline, set_sdk_version, set_verbose,
+};
+pub use watch::watch;
+pub use wrapper_framework::build_wrapper_xcframework;
+pub use xcode::{set_developer_dir, set_toolchain};
+pub use xcframework::{package_xcframework, ApplePlatform, FrameworkLayout};
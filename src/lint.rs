@@ -0,0 +1,59 @@
+//! SwiftLint over generated and vendored Swift sources.
+//!
+//! The wrapper files this tool generates (and the Swift sources it vendors
+//! from dependency repositories) end up compiled inside the host app, where
+//! that app's own SwiftLint run will flag them. Running the linter here, with
+//! a baseline recorded on the first run, surfaces *new* problems in generated
+//! code before the host app's CI does, without failing on violations the
+//! generator has always produced.
+
+use std::process::Command;
+
+use anyhow::Result;
+use camino::Utf8PathBuf;
+
+use crate::project::Project;
+use crate::utils::ExecuteCommand;
+
+/// Run SwiftLint over the generated wrapper files and the vendored Swift
+/// sources, comparing against a baseline generated on the first run. Does
+/// nothing when the workspace has no `.swiftlint.yml`, so projects that
+/// don't use SwiftLint aren't forced to install it. Pass `update_baseline`
+/// to re-record the current violations as the new baseline.
+pub fn lint(update_baseline: bool) -> crate::Result<()> {
+    let run = || -> Result<()> {
+        let project = Project::from_current_dir()?;
+        let config = project.workspace_root().join(".swiftlint.yml");
+        if !config.exists() {
+            println!("No .swiftlint.yml in the workspace root, skipping lint");
+            return Ok(());
+        }
+        let directories: Vec<Utf8PathBuf> = [
+            project.swift_wrapper_dir(),
+            project.vendored_sources_dir(),
+        ]
+        .into_iter()
+        .filter(|directory| directory.exists())
+        .collect();
+        if directories.is_empty() {
+            println!("No generated or vendored Swift sources to lint yet");
+            return Ok(());
+        }
+        let baseline = project.output_root().join("swiftlint-baseline.json");
+        let mut cmd = Command::new("swiftlint");
+        cmd.args(["lint", "--strict", "--config", config.as_str()]);
+        if update_baseline || !baseline.exists() {
+            // The first run records every existing violation, so only
+            // regressions fail from then on.
+            cmd.args(["--write-baseline", baseline.as_str()]);
+        } else {
+            cmd.args(["--baseline", baseline.as_str()]);
+        }
+        for directory in &directories {
+            cmd.arg(directory);
+        }
+        cmd.successful_output()?;
+        Ok(())
+    };
+    run().map_err(crate::Error::from)
+}
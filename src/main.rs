@@ -0,0 +1,891 @@
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use camino::Utf8PathBuf;
+use clap::{Parser, Subcommand};
+use indicatif::{HumanBytes, ProgressBar, ProgressStyle};
+use uniffi_swift_helper::{
+    bench, bloat, build, build_framework, build_wrapper_xcframework, cache_key, compare, coverage,
+    fingerprint, generate_bazel, generate_example, generate_swift_package, generate_test_scaffolds,
+    generate_tuist, integrate,
+    lint, watch,
+    ApplePlatform,
+    BuildEvent, BuildOptions, BuildStage, Error, FrameworkLayout, GeneratePackageOptions, Reporter,
+    notarize, package_artifact_bundle, package_xcframework, regenerate_bindings, release,
+    show_config,
+    vendor_swift_sources,
+    verify_min_os,
+    verify_reproducible,
+    verify_swift_package,
+    DSYM_UPLOADER_ENV,
+};
+
+#[derive(Parser)]
+#[command(name = "uniffi-swift-helper", version, about)]
+struct Cli {
+    /// Stream subprocess output (cargo, xcodebuild, …) live, prefixed with
+    /// the tool name, instead of only showing it on failure.
+    #[arg(long, short, global = true)]
+    verbose: bool,
+
+    /// Print the commands that would run without executing them.
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    /// Run every cargo invocation with --offline, for hermetic builds. Fails
+    /// instead of fetching when something isn't available locally.
+    #[arg(long, global = true)]
+    offline: bool,
+
+    /// Run every cargo invocation (including cargo metadata) with --locked,
+    /// failing loudly if Cargo.lock would change.
+    #[arg(long, global = true)]
+    locked: bool,
+
+    /// Run every cargo invocation with --frozen: --locked plus no network.
+    #[arg(long, global = true)]
+    frozen: bool,
+
+    /// Kill any subprocess that runs longer than this many seconds.
+    #[arg(long, global = true, value_name = "SECONDS")]
+    command_timeout: Option<u64>,
+
+    /// Use the Xcode at this path (sets DEVELOPER_DIR for every xcodebuild
+    /// and xcrun invocation).
+    #[arg(long, global = true, value_name = "PATH")]
+    developer_dir: Option<Utf8PathBuf>,
+
+    /// Build against this SDK version (e.g. a beta SDK's) instead of the
+    /// selected Xcode's default: cargo builds get a matching SDKROOT and
+    /// xcodebuild invocations a -sdk argument.
+    #[arg(long, global = true, value_name = "VERSION")]
+    sdk_version: Option<String>,
+
+    /// Use this Swift toolchain for every invocation (sets TOOLCHAINS to the
+    /// identifier from the toolchain's Info.plist).
+    #[arg(long, global = true, value_name = "IDENTIFIER")]
+    toolchain: Option<String>,
+
+    /// Append a timestamped record of every executed command and its full
+    /// output to this file, independent of console verbosity.
+    #[arg(long, global = true, value_name = "PATH")]
+    log_file: Option<Utf8PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Build the UniFFI crates for the given platforms and assemble the
+    /// XCFramework and Swift bindings.
+    Build {
+        /// Platform to build for. Can be repeated; defaults to all platforms.
+        #[arg(long, value_enum)]
+        platform: Vec<ApplePlatform>,
+
+        /// Cargo profile to build with.
+        #[arg(long, default_value = "release")]
+        profile: String,
+
+        /// Extract dSYMs after packaging and upload each one with this
+        /// command (split on whitespace; the dSYM path is appended).
+        /// Defaults to $UNIFFI_SWIFT_HELPER_DSYM_UPLOADER when set.
+        #[arg(long, value_name = "COMMAND")]
+        upload_dsyms_with: Option<String>,
+
+        /// Build for dead-code stripping: disables `link-dead-code` and emits
+        /// per-function/per-data sections so `-dead_strip` can shrink the
+        /// final app.
+        #[arg(long)]
+        strip_dead_code: bool,
+
+        /// Only build this UniFFI package. Can be repeated; defaults to all
+        /// UniFFI packages in the workspace.
+        #[arg(long = "package", value_name = "NAME")]
+        packages: Vec<String>,
+
+        /// Clang module name for the generated FFI headers. Defaults to what
+        /// uniffi-bindgen derives from the crate configuration.
+        #[arg(long, value_name = "NAME")]
+        module_name: Option<String>,
+
+        /// Filename for the generated module map.
+        #[arg(long, value_name = "FILENAME")]
+        modulemap_filename: Option<String>,
+
+        /// Don't scan dependencies for UniFFI metadata. Faster when all
+        /// exported APIs live in the UniFFI packages themselves.
+        #[arg(long)]
+        metadata_no_deps: bool,
+
+        /// Package one merged XCFramework, or one per UniFFI crate.
+        #[arg(long, value_enum, default_value_t)]
+        layout: FrameworkLayout,
+
+        /// Read minimum OS versions (IPHONEOS_DEPLOYMENT_TARGET etc.) from
+        /// this .xcodeproj or xcconfig file and apply them to the Rust builds.
+        #[arg(long, value_name = "PATH")]
+        deployment_targets_from: Option<Utf8PathBuf>,
+
+        /// Fail instead of warning when a generated wrapper file was edited
+        /// by hand since the last run.
+        #[arg(long)]
+        strict: bool,
+
+        /// Install the nightly toolchain and rust-src via rustup when a
+        /// tier-3 platform needs them and they are missing.
+        #[arg(long)]
+        install_missing_toolchain: bool,
+
+        /// Experimental: cross-compile from a Linux host via cargo-zigbuild
+        /// and llvm-lipo. Needs SDKROOT pointing at an Apple SDK.
+        #[arg(long)]
+        zigbuild: bool,
+
+        /// Lay out the XCFramework natively instead of running
+        /// `xcodebuild -create-xcframework`.
+        #[arg(long)]
+        no_xcodebuild: bool,
+
+        /// Compile the generated bindings with -emit-module-interface and
+        /// ship the .swiftinterface/.swiftmodule files in the XCFramework.
+        #[arg(long)]
+        emit_module_interface: bool,
+
+        /// Fail if Cargo.lock is missing or would change, so git dependencies
+        /// can't float to a newer commit during a release build.
+        #[arg(long)]
+        require_locked: bool,
+
+        /// Stamp each slice with vtool -set-build-version before assembly,
+        /// for tier-3 builds whose objects lack platform metadata.
+        #[arg(long)]
+        fix_build_version: bool,
+
+        /// Generate the Swift bindings with this external uniffi-bindgen
+        /// command (e.g. "cargo run -p uniffi-bindgen --") instead of the
+        /// bundled uniffi_bindgen library.
+        #[arg(long, value_name = "COMMAND")]
+        bindgen_with: Option<String>,
+
+        /// Pass --timings to each cargo build and collect the HTML reports
+        /// into the artifacts directory, named per package and target.
+        #[arg(long)]
+        cargo_timings: bool,
+
+        /// Keep building the remaining targets when one fails, summarize
+        /// the failures, and package only the successful subset.
+        #[arg(long)]
+        keep_going: bool,
+
+        /// Print the end-of-run summary as JSON instead of a table.
+        #[arg(long)]
+        json: bool,
+
+        /// Package both a static and a dynamic XCFramework variant from the
+        /// same set of Rust builds. Requires the merged layout and a crate
+        /// whose `crate-type` lists both `staticlib` and `cdylib`.
+        #[arg(long)]
+        linkage_variants: bool,
+
+        /// Fast path for day-to-day development: build only the arm64 iOS
+        /// simulator and arm64 macOS slices and assemble a minimal
+        /// XCFramework, skipping device, Intel, watch, and TV builds.
+        #[arg(long, conflicts_with = "platform")]
+        dev_fast: bool,
+
+        /// First pipeline stage to run, assuming earlier stages' outputs are
+        /// in place; e.g. --from package resumes after a packaging failure.
+        #[arg(long, value_enum, value_name = "STAGE")]
+        from: Option<BuildStage>,
+
+        /// Last pipeline stage to run; later stages are skipped.
+        #[arg(long, value_enum, value_name = "STAGE")]
+        until: Option<BuildStage>,
+    },
+    /// Build a .framework bundle for a single platform, for consumers that
+    /// embed a plain framework instead of an XCFramework.
+    BuildFramework {
+        /// Platform to build the framework for (device slices only).
+        #[arg(long, value_enum, default_value = "macos")]
+        platform: ApplePlatform,
+
+        /// Cargo profile to build with.
+        #[arg(long, default_value = "release")]
+        profile: String,
+
+        /// Bundle the crates' cdylib output as a dynamic framework instead
+        /// of the static library. Needs "cdylib" in the crate-type list.
+        #[arg(long)]
+        dynamic: bool,
+    },
+    /// Regenerate the Swift bindings and processed wrappers from already
+    /// built libraries, without re-running cargo.
+    GenerateBindings {
+        /// Platform whose built libraries to generate from.
+        #[arg(long, value_enum, default_value = "macos")]
+        platform: ApplePlatform,
+
+        /// Cargo profile the libraries were built with.
+        #[arg(long, default_value = "release")]
+        profile: String,
+
+        /// Only generate for this UniFFI package. Can be repeated; defaults
+        /// to all UniFFI packages in the workspace.
+        #[arg(long = "package", value_name = "NAME")]
+        packages: Vec<String>,
+
+        /// The layout the build used.
+        #[arg(long, value_enum, default_value_t)]
+        layout: FrameworkLayout,
+
+        /// Clang module name for the generated FFI headers.
+        #[arg(long, value_name = "NAME")]
+        module_name: Option<String>,
+
+        /// Filename for the generated module map.
+        #[arg(long, value_name = "FILENAME")]
+        modulemap_filename: Option<String>,
+
+        /// Don't scan dependencies for UniFFI metadata.
+        #[arg(long)]
+        metadata_no_deps: bool,
+
+        /// Fail instead of warning when a generated wrapper file was edited
+        /// by hand since the last run.
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Assemble the XCFramework from already-built per-target libraries and
+    /// binding directories, e.g. downloaded from CI matrix jobs.
+    PackageXcframework {
+        /// Platform whose slices to include. Can be repeated; defaults to
+        /// all platforms.
+        #[arg(long, value_enum)]
+        platform: Vec<ApplePlatform>,
+
+        /// Cargo profile the libraries were built with.
+        #[arg(long, default_value = "release")]
+        profile: String,
+
+        /// Only package this UniFFI package. Can be repeated; defaults to
+        /// all UniFFI packages in the workspace.
+        #[arg(long = "package", value_name = "NAME")]
+        packages: Vec<String>,
+
+        /// Package one merged XCFramework, or one per UniFFI crate.
+        #[arg(long, value_enum, default_value_t)]
+        layout: FrameworkLayout,
+
+        /// Lay out the XCFramework natively instead of running
+        /// `xcodebuild -create-xcframework`.
+        #[arg(long)]
+        no_xcodebuild: bool,
+
+        /// Stamp each slice with vtool -set-build-version before assembly.
+        #[arg(long)]
+        fix_build_version: bool,
+
+        /// Read minimum OS versions from this .xcodeproj or xcconfig file,
+        /// for --fix-build-version.
+        #[arg(long, value_name = "PATH")]
+        deployment_targets_from: Option<Utf8PathBuf>,
+    },
+    /// Generate Package.swift for the workspace's Swift wrapper packages.
+    GeneratePackage(GeneratePackageArgs),
+    /// Check that the committed Package.swift matches what generate-package
+    /// would produce, for CI. Fails with a diff when it drifted.
+    VerifyPackage(GeneratePackageArgs),
+    /// Scaffold a minimal XCTest target for every package without a Tests
+    /// directory, and regenerate Package.swift to include them.
+    GenerateTests(GeneratePackageArgs),
+    /// Add or update the SDK dependency in a consumer's Package.swift,
+    /// pointing it at a local path or a released version.
+    Integrate {
+        /// The consumer Package.swift to rewrite.
+        manifest: Utf8PathBuf,
+
+        /// Depend on the SDK by local path.
+        #[arg(long, conflicts_with = "url")]
+        path: Option<Utf8PathBuf>,
+
+        /// Depend on the released SDK at this git URL (needs --version).
+        #[arg(long)]
+        url: Option<String>,
+
+        /// The released version to require, as a `from:` constraint.
+        #[arg(long, requires = "url")]
+        version: Option<String>,
+    },
+    /// Build a workspace Rust bin target for both macOS architectures and
+    /// package it as a SwiftPM .artifactbundle, for build-tool plugins.
+    ArtifactBundle {
+        /// The bin target to package.
+        bin: String,
+
+        /// Cargo profile to build with.
+        #[arg(long, default_value = "release")]
+        profile: String,
+    },
+    /// Generate rules_swift/rules_apple BUILD targets for the SDK, for
+    /// monorepos that consume it through Bazel.
+    GenerateBazel {
+        /// The layout the XCFrameworks were built with.
+        #[arg(long, value_enum, default_value_t)]
+        layout: FrameworkLayout,
+
+        /// Where to write the BUILD file (default: BUILD.bazel at the
+        /// workspace root).
+        #[arg(long, value_name = "PATH")]
+        output: Option<Utf8PathBuf>,
+    },
+    /// Generate a Tuist ProjectDescription helper listing the SDK's wrapper
+    /// products and XCFramework paths, for host apps that use Tuist.
+    GenerateTuist {
+        /// The layout the XCFrameworks were built with.
+        #[arg(long, value_enum, default_value_t)]
+        layout: FrameworkLayout,
+
+        /// Where to write the helper (default: Tuist/Dependencies.swift at
+        /// the workspace root).
+        #[arg(long, value_name = "PATH")]
+        output: Option<Utf8PathBuf>,
+    },
+    /// Generate a runnable SwiftUI example package under Example/ that
+    /// depends on the SDK, for evaluators to try the bindings.
+    Example {
+        /// Overwrite an existing Example directory.
+        #[arg(long)]
+        force: bool,
+    },
+    /// Build the macOS slice in release and run the XCTest benchmark classes
+    /// from the packages' Tests directories, summarizing the averages.
+    Bench {
+        /// Only run benchmark classes matching this `swift test --filter`
+        /// pattern.
+        #[arg(long, default_value = "Benchmarks")]
+        filter: String,
+    },
+    /// Build the macOS slice with coverage instrumentation, run the Swift
+    /// test suite against it, and report Rust-side coverage via llvm-cov.
+    Coverage {
+        /// Report coverage of the Rust code the Swift tests exercise. This
+        /// is currently the only mode, so the flag is accepted for clarity.
+        #[arg(long)]
+        rust: bool,
+        /// Only run tests matching this `swift test --filter` pattern.
+        #[arg(long)]
+        filter: Option<String>,
+    },
+    /// Refresh the vendored Swift sources of out-of-workspace UniFFI
+    /// packages under target/swift-vendored.
+    Vendor {
+        /// Only report which vendored trees are stale, without refreshing
+        /// them. Fails when any is out of date, for CI.
+        #[arg(long)]
+        check: bool,
+    },
+    /// Report symbol sizes of the built static libraries, grouped by crate.
+    Bloat {
+        /// Platform whose slices to analyze. Can be repeated; defaults to all
+        /// platforms.
+        #[arg(long, value_enum)]
+        platform: Vec<ApplePlatform>,
+
+        /// Cargo profile the libraries were built with.
+        #[arg(long, default_value = "release")]
+        profile: String,
+
+        /// Emit the report as JSON instead of a table.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print a stable hash of the built XCFramework's FFI surface (headers
+    /// and module maps), and record it next to the artifacts.
+    Fingerprint,
+    /// Print a stable hash of everything affecting the build output, for use
+    /// as a CI cache key.
+    CacheKey {
+        /// Platform set the cached build covers. Can be repeated; defaults to
+        /// all platforms.
+        #[arg(long, value_enum)]
+        platform: Vec<ApplePlatform>,
+
+        /// Cargo profile the cached build uses.
+        #[arg(long, default_value = "release")]
+        profile: String,
+    },
+    /// Watch the Rust sources and rebuild one slice (plus bindings and
+    /// wrappers) on every change.
+    Watch {
+        /// Platform whose native slice to rebuild.
+        #[arg(long, value_enum, default_value = "macos")]
+        platform: ApplePlatform,
+
+        /// Cargo profile to build with.
+        #[arg(long, default_value = "dev")]
+        profile: String,
+    },
+    /// Compare the built XCFramework against a previous release and fail on
+    /// breaking ABI changes (removed symbols, changed header declarations).
+    Compare {
+        /// The previously released XCFramework to compare against.
+        #[arg(long, value_name = "PATH")]
+        previous: Utf8PathBuf,
+
+        /// The freshly built XCFramework. Defaults to the workspace's
+        /// target/<ffi_module_name>.xcframework.
+        #[arg(long, value_name = "PATH")]
+        current: Option<Utf8PathBuf>,
+    },
+    /// Submit an artifact to Apple's notary service and staple the ticket,
+    /// so directly distributed frameworks pass Gatekeeper.
+    Notarize {
+        /// The framework, XCFramework, or zip to notarize.
+        path: Utf8PathBuf,
+
+        /// Keychain profile holding the App Store Connect credentials, as
+        /// stored with `xcrun notarytool store-credentials`.
+        #[arg(long, value_name = "NAME")]
+        keychain_profile: String,
+    },
+    /// Build the XCFramework twice and fail when the artifacts differ,
+    /// naming each file that isn't byte-for-byte reproducible.
+    VerifyReproducible {
+        /// Platform to build for. Can be repeated; defaults to all platforms.
+        #[arg(long, value_enum)]
+        platform: Vec<ApplePlatform>,
+
+        /// Cargo profile to build with.
+        #[arg(long, default_value = "release")]
+        profile: String,
+
+        /// Run the second build in a scratch target directory instead of
+        /// reusing the first build's incremental artifacts.
+        #[arg(long)]
+        clean: bool,
+    },
+    /// Check that the built slices' embedded minimum OS versions don't
+    /// exceed the deployment targets of a consumer project.
+    VerifyMinOs {
+        /// Platform to verify. Can be repeated; defaults to all platforms.
+        #[arg(long, value_enum)]
+        platform: Vec<ApplePlatform>,
+
+        /// Cargo profile the slices were built with.
+        #[arg(long, default_value = "release")]
+        profile: String,
+
+        /// The .xcodeproj or xcconfig file declaring the deployment targets.
+        #[arg(long)]
+        deployment_targets_from: Utf8PathBuf,
+    },
+    /// Print the fully resolved configuration: paths, packages, module
+    /// names, platform matrix, and which uniffi.toml supplied each setting.
+    Config {
+        /// Also resolve and print the deployment targets from this
+        /// .xcodeproj or xcconfig file.
+        #[arg(long, value_name = "PATH")]
+        deployment_targets_from: Option<Utf8PathBuf>,
+    },
+    /// Run SwiftLint over the generated wrapper files and vendored Swift
+    /// sources, failing only on violations newer than the recorded baseline.
+    Lint {
+        /// Re-record the current violations as the baseline instead of
+        /// comparing against it.
+        #[arg(long)]
+        update_baseline: bool,
+    },
+    /// Cut a release: bump the crate versions, archive the XCFramework with
+    /// its checksum, and point the manifest at the release URL.
+    Release {
+        /// The version to release, e.g. 1.4.0.
+        version: String,
+
+        /// Also create an annotated `v<version>` git tag.
+        #[arg(long)]
+        tag: bool,
+    },
+    /// Compile the Swift wrapper modules into their own XCFrameworks with
+    /// library evolution, for fully binary SDK distribution.
+    BuildWrapper {
+        /// Platform to build for. Can be repeated; defaults to all platforms.
+        #[arg(long, value_enum)]
+        platform: Vec<ApplePlatform>,
+    },
+}
+
+#[derive(clap::Args)]
+struct GeneratePackageArgs {
+    /// Only generate targets for this UniFFI package. Can be repeated;
+    /// defaults to all UniFFI packages in the workspace.
+    #[arg(long = "package", value_name = "NAME")]
+    packages: Vec<String>,
+
+    /// The layout the XCFrameworks were built with.
+    #[arg(long, value_enum, default_value_t)]
+    layout: FrameworkLayout,
+
+    /// Also generate an umbrella `<FfiModuleName>Kit` product that
+    /// re-exports every public module.
+    #[arg(long)]
+    umbrella: bool,
+
+    /// Read minimum OS versions from this .xcodeproj or xcconfig file and
+    /// declare them as the package's platforms.
+    #[arg(long, value_name = "PATH")]
+    deployment_targets_from: Option<Utf8PathBuf>,
+
+    /// Emit placeholder bindings targets when the wrappers haven't been
+    /// generated yet, instead of failing.
+    #[arg(long)]
+    allow_missing_wrappers: bool,
+
+    /// Symlink the Swift sources of path dependencies instead of copying
+    /// them, so edits in the dependency repo are picked up immediately.
+    #[arg(long)]
+    symlink_vendored: bool,
+
+    /// swift-format configuration file to format the manifest with, for
+    /// teams whose .swift-format lives in a non-standard location.
+    #[arg(long, value_name = "PATH")]
+    format_config: Option<Utf8PathBuf>,
+
+    /// Also run swift-format over the generated wrapper sources.
+    #[arg(long)]
+    format_wrappers: bool,
+
+    /// Also generate a smoke-test target that verifies the bindings match
+    /// the compiled Rust library, so contract mismatches fail `swift test`.
+    #[arg(long)]
+    smoke_test: bool,
+}
+
+impl GeneratePackageArgs {
+    fn into_options(self) -> GeneratePackageOptions {
+        GeneratePackageOptions {
+            packages: self.packages,
+            layout: self.layout,
+            umbrella: self.umbrella,
+            deployment_targets_from: self.deployment_targets_from,
+            allow_missing_wrappers: self.allow_missing_wrappers,
+            symlink_vendored: self.symlink_vendored,
+            format_config: self.format_config,
+            format_wrappers: self.format_wrappers,
+            smoke_test: self.smoke_test,
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    uniffi_swift_helper::set_verbose(cli.verbose);
+    uniffi_swift_helper::set_dry_run(cli.dry_run);
+    uniffi_swift_helper::set_offline(cli.offline);
+    uniffi_swift_helper::set_cargo_locked(cli.locked);
+    uniffi_swift_helper::set_cargo_frozen(cli.frozen);
+    uniffi_swift_helper::set_command_timeout(
+        cli.command_timeout.map(std::time::Duration::from_secs),
+    );
+    if let Some(developer_dir) = &cli.developer_dir {
+        uniffi_swift_helper::set_developer_dir(developer_dir);
+    }
+    if let Some(sdk_version) = &cli.sdk_version {
+        uniffi_swift_helper::set_sdk_version(sdk_version);
+    }
+    if let Some(toolchain) = &cli.toolchain {
+        uniffi_swift_helper::set_toolchain(toolchain);
+    }
+    if let Some(log_file) = &cli.log_file {
+        uniffi_swift_helper::set_log_file(log_file);
+    }
+    let result = match cli.command {
+        Command::Build {
+            platform,
+            profile,
+            upload_dsyms_with,
+            strip_dead_code,
+            packages,
+            module_name,
+            modulemap_filename,
+            metadata_no_deps,
+            layout,
+            deployment_targets_from,
+            strict,
+            install_missing_toolchain,
+            zigbuild,
+            no_xcodebuild,
+            emit_module_interface,
+            require_locked,
+            fix_build_version,
+            bindgen_with,
+            cargo_timings,
+            keep_going,
+            json,
+            linkage_variants,
+            dev_fast,
+            from,
+            until,
+        } => {
+            let platforms = if dev_fast {
+                vec![ApplePlatform::IOS, ApplePlatform::MacOS]
+            } else if platform.is_empty() {
+                ApplePlatform::all()
+            } else {
+                platform
+            };
+            let options = BuildOptions {
+                dsym_uploader: dsym_uploader(upload_dsyms_with),
+                strip_dead_code,
+                packages,
+                module_name,
+                modulemap_filename,
+                metadata_no_deps,
+                layout,
+                deployment_targets_from,
+                strict,
+                install_missing_toolchain,
+                zigbuild,
+                no_xcodebuild,
+                emit_module_interface,
+                require_locked,
+                fix_build_version,
+                bindgen_command: split_command(bindgen_with),
+                cargo_timings,
+                keep_going,
+                json_summary: json,
+                linkage_variants,
+                dev_fast,
+                from,
+                until,
+            };
+            build(&platforms, &profile, &options, &progress_bar_reporter())
+        }
+        Command::BuildFramework {
+            platform,
+            profile,
+            dynamic,
+        } => {
+            build_framework(platform, &profile, dynamic, &progress_bar_reporter())
+        }
+        Command::GenerateBindings {
+            platform,
+            profile,
+            packages,
+            layout,
+            module_name,
+            modulemap_filename,
+            metadata_no_deps,
+            strict,
+        } => {
+            let options = BuildOptions {
+                packages,
+                module_name,
+                modulemap_filename,
+                metadata_no_deps,
+                layout,
+                strict,
+                ..BuildOptions::default()
+            };
+            regenerate_bindings(platform, &profile, &options, &progress_bar_reporter())
+        }
+        Command::PackageXcframework {
+            platform,
+            profile,
+            packages,
+            layout,
+            no_xcodebuild,
+            fix_build_version,
+            deployment_targets_from,
+        } => {
+            let platforms = if platform.is_empty() {
+                ApplePlatform::all()
+            } else {
+                platform
+            };
+            let options = BuildOptions {
+                packages,
+                layout,
+                no_xcodebuild,
+                fix_build_version,
+                deployment_targets_from,
+                ..BuildOptions::default()
+            };
+            package_xcframework(&platforms, &profile, &options, &progress_bar_reporter())
+        }
+        Command::GeneratePackage(args) => generate_swift_package(&args.into_options()),
+        Command::VerifyPackage(args) => verify_swift_package(&args.into_options()),
+        Command::GenerateTests(args) => generate_test_scaffolds(&args.into_options()),
+        Command::Integrate {
+            manifest,
+            path,
+            url,
+            version,
+        } => integrate(&manifest, path.as_deref(), url.as_deref(), version.as_deref()),
+        Command::ArtifactBundle { bin, profile } => package_artifact_bundle(&bin, &profile),
+        Command::GenerateBazel { layout, output } => generate_bazel(layout, output.as_deref()),
+        Command::GenerateTuist { layout, output } => generate_tuist(layout, output.as_deref()),
+        Command::Example { force } => generate_example(force),
+        Command::Vendor { check } => vendor_swift_sources(check),
+        Command::Bench { filter } => bench(&filter, &progress_bar_reporter()),
+        Command::Coverage { rust, filter } => {
+            coverage(rust, filter.as_deref(), &progress_bar_reporter())
+        }
+        Command::Bloat {
+            platform,
+            profile,
+            json,
+        } => {
+            let platforms = if platform.is_empty() {
+                ApplePlatform::all()
+            } else {
+                platform
+            };
+            bloat(&platforms, &profile, json)
+        }
+        Command::Fingerprint => fingerprint(),
+        Command::CacheKey { platform, profile } => {
+            let platforms = if platform.is_empty() {
+                ApplePlatform::all()
+            } else {
+                platform
+            };
+            cache_key(&platforms, &profile)
+        }
+        Command::Watch { platform, profile } => {
+            watch(platform, &profile, &progress_bar_reporter())
+        }
+        Command::Compare { previous, current } => compare(&previous, current.as_deref()),
+        Command::Notarize {
+            path,
+            keychain_profile,
+        } => notarize(&path, &keychain_profile),
+        Command::VerifyReproducible {
+            platform,
+            profile,
+            clean,
+        } => {
+            let platforms = if platform.is_empty() {
+                ApplePlatform::all()
+            } else {
+                platform
+            };
+            verify_reproducible(
+                &platforms,
+                &profile,
+                &BuildOptions::default(),
+                clean,
+                &progress_bar_reporter(),
+            )
+        }
+        Command::VerifyMinOs {
+            platform,
+            profile,
+            deployment_targets_from,
+        } => {
+            let platforms = if platform.is_empty() {
+                ApplePlatform::all()
+            } else {
+                platform
+            };
+            verify_min_os(&platforms, &profile, &deployment_targets_from)
+        }
+        Command::Config {
+            deployment_targets_from,
+        } => show_config(deployment_targets_from.as_deref()),
+        Command::Lint { update_baseline } => lint(update_baseline),
+        Command::Release { version, tag } => release(&version, tag),
+        Command::BuildWrapper { platform } => {
+            let platforms = if platform.is_empty() {
+                ApplePlatform::all()
+            } else {
+                platform
+            };
+            build_wrapper_xcframework(&platforms)
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            match &error {
+                // For wrapped anyhow errors, `:#` prints the whole context chain.
+                Error::Other(inner) => eprintln!("Error: {inner:#}"),
+                typed => eprintln!("Error: {typed}"),
+            }
+            if let Some(log_file) = &cli.log_file {
+                eprintln!("Full command log: {log_file}");
+            }
+            ExitCode::from(exit_code(&error))
+        }
+    }
+}
+
+/// Distinct exit codes per failure cause, so scripts can react without
+/// parsing stderr.
+fn exit_code(error: &Error) -> u8 {
+    match error {
+        Error::CommandFailed { .. } => 2,
+        Error::MissingToolchain { .. } => 3,
+        Error::MissingUniffiTomlKey { .. } => 4,
+        Error::NotWorkspaceRoot { .. } => 5,
+        Error::NoUniffiPackages => 6,
+        _ => 1,
+    }
+}
+
+/// Resolve the dSYM uploader command from the CLI flag or the environment.
+fn dsym_uploader(flag: Option<String>) -> Option<Vec<String>> {
+    split_command(flag.or_else(|| std::env::var(DSYM_UPLOADER_ENV).ok()))
+}
+
+/// Split a whitespace-separated command-line flag into argv form.
+fn split_command(flag: Option<String>) -> Option<Vec<String>> {
+    let argv: Vec<String> = flag?.split_whitespace().map(str::to_string).collect();
+    if argv.is_empty() {
+        None
+    } else {
+        Some(argv)
+    }
+}
+
+/// A [`Reporter`] that renders build phases as progress bars.
+fn progress_bar_reporter() -> Reporter {
+    let bar: Mutex<Option<ProgressBar>> = Mutex::new(None);
+    let copied = AtomicU64::new(0);
+    Reporter::new(move |event| {
+        let mut bar = bar.lock().expect("progress bar lock poisoned");
+        match event {
+            BuildEvent::PhaseStarted { phase, steps } => {
+                let progress = ProgressBar::new(*steps as u64).with_prefix(phase.to_string());
+                progress.set_style(
+                    ProgressStyle::with_template(
+                        "{prefix} [{bar:40.cyan/blue}] {pos}/{len} {wide_msg}",
+                    )
+                    .expect("progress bar template is valid")
+                    .progress_chars("=> "),
+                );
+                *bar = Some(progress);
+            }
+            BuildEvent::StepFinished { step, .. } => {
+                if let Some(progress) = bar.as_ref() {
+                    progress.inc(1);
+                    progress.set_message(step.clone());
+                }
+            }
+            BuildEvent::PhaseFinished { phase } => {
+                if let Some(progress) = bar.take() {
+                    progress.finish_and_clear();
+                }
+                println!("{phase}: done");
+            }
+            BuildEvent::BytesCopied { bytes } => {
+                let total = copied.fetch_add(*bytes, Ordering::Relaxed) + bytes;
+                if let Some(progress) = bar.as_ref() {
+                    progress.set_message(format!("{} copied", HumanBytes(total)));
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,121 @@
+//! Minimum OS version verification.
+//!
+//! App Store validation rejects binaries whose embedded minimum OS exceeds
+//! the app's deployment target, and a dependency can bump its minimum
+//! silently through a version update. `verify-min-os` reads the
+//! `LC_BUILD_VERSION` metadata out of every built slice with `vtool` and
+//! fails when it demands more than the configured deployment targets, so the
+//! mismatch surfaces in CI instead of at submission time.
+
+
+use anyhow::{bail, Result};
+use camino::Utf8Path;
+
+use crate::build::{profile_dir_name, CrateType};
+use crate::deployment::{version_less_than, DeploymentTargets};
+use crate::project::Project;
+use crate::utils::ExecuteCommand;
+use crate::xcframework::{ApplePlatform, Slice};
+
+/// Check that no built slice's embedded minimum OS version exceeds the
+/// deployment targets read from `deployment_targets_from` (an `.xcodeproj`
+/// or xcconfig file). Platforms without a configured target are skipped.
+pub fn verify_min_os(
+    platforms: &[ApplePlatform],
+    profile: &str,
+    deployment_targets_from: &Utf8Path,
+) -> crate::Result<()> {
+    let run = || -> Result<()> {
+        let project = Project::from_current_dir()?;
+        let targets = DeploymentTargets::from_path(deployment_targets_from)?;
+        let profile_dir = profile_dir_name(profile);
+        let mut violations = Vec::new();
+        for platform in platforms {
+            let Some(configured) = targets.version(deployment_setting(*platform)) else {
+                println!(
+                    "{}: no deployment target configured, skipping",
+                    platform.name()
+                );
+                continue;
+            };
+            for triple in platform.target_triples() {
+                let slice = Slice::create(&project, triple, profile_dir, CrateType::Staticlib)?;
+                let output = crate::utils::xcrun_tool("vtool")
+                    .args(["-show-build", slice.library_path.as_str()])
+                    .successful_output()?;
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                let Some(minos) = max_minos(&stdout) else {
+                    // Handled separately: see `vtool -set-build-version`
+                    // fix-ups for tier-3 targets.
+                    println!("{triple}: no build-version metadata in {}", slice.library_path);
+                    continue;
+                };
+                if version_less_than(configured, &minos) {
+                    violations.push(format!(
+                        "{triple}: embedded minos {minos} exceeds the configured {configured}"
+                    ));
+                } else {
+                    println!("{triple}: minos {minos} (configured {configured})");
+                }
+            }
+        }
+        if !violations.is_empty() {
+            bail!(
+                "Minimum OS versions exceed the deployment targets:\n{}",
+                violations.join("\n")
+            );
+        }
+        Ok(())
+    };
+    run().map_err(crate::Error::from)
+}
+
+/// The build setting governing a platform's minimum OS version.
+fn deployment_setting(platform: ApplePlatform) -> &'static str {
+    match platform {
+        ApplePlatform::IOS => "IPHONEOS_DEPLOYMENT_TARGET",
+        ApplePlatform::MacOS => "MACOSX_DEPLOYMENT_TARGET",
+        ApplePlatform::TvOS => "TVOS_DEPLOYMENT_TARGET",
+        ApplePlatform::WatchOS => "WATCHOS_DEPLOYMENT_TARGET",
+    }
+}
+
+/// The highest `minos` value `vtool -show-build` reports across an archive's
+/// objects. Static archives print one load command block per member; the
+/// most demanding one is what matters to the consumer's linker.
+fn max_minos(output: &str) -> Option<String> {
+    output
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("minos "))
+        .map(str::trim)
+        .max_by(|a, b| {
+            if version_less_than(a, b) {
+                std::cmp::Ordering::Less
+            } else if version_less_than(b, a) {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        })
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn max_minos_takes_the_most_demanding_object() {
+        let output = "\
+            libfoo.a(foo.o):\n\
+            Load command 1\n\
+                  cmd LC_BUILD_VERSION\n\
+              platform IOS\n\
+                 minos 15.0\n\
+                   sdk 17.2\n\
+            libfoo.a(bar.o):\n\
+                 minos 16.4\n";
+        assert_eq!(max_minos(output), Some("16.4".to_string()));
+        assert_eq!(max_minos("no load commands here"), None);
+    }
+}
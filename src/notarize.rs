@@ -0,0 +1,64 @@
+//! Notarization of distributable artifacts.
+//!
+//! XCFrameworks consumed through SwiftPM don't need notarization, but teams
+//! that hand out zipped frameworks directly do — without a notarization
+//! ticket, Gatekeeper blocks the binary on consumers' machines. This wraps
+//! `notarytool submit` and `stapler staple` so the release script stays a
+//! one-liner.
+
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+
+use crate::utils::ExecuteCommand;
+
+/// Submit an artifact to Apple's notary service and staple the ticket.
+///
+/// `keychain_profile` names credentials previously stored with
+/// `xcrun notarytool store-credentials`. Directories (.framework,
+/// .xcframework) are zipped before submission, since notarytool only accepts
+/// archive formats; the ticket is stapled to the original artifact.
+pub fn notarize(path: &Utf8Path, keychain_profile: &str) -> crate::Result<()> {
+    let run = || -> Result<()> {
+        if !path.exists() {
+            bail!("{path} does not exist");
+        }
+
+        let submission = if path.is_dir() {
+            let archive = Utf8PathBuf::from(format!("{path}.zip"));
+            // ditto preserves the symlinks and resource forks that a
+            // .framework bundle relies on; plain zip would break codesigning.
+            Command::new("ditto")
+                .args(["-c", "-k", "--keepParent", path.as_str(), archive.as_str()])
+                .successful_output()
+                .context("Can't zip the artifact for submission")?;
+            archive
+        } else {
+            path.to_owned()
+        };
+
+        println!("Submitting {submission} for notarization…");
+        crate::utils::xcrun_tool("notarytool")
+            .args(["submit", submission.as_str()])
+            .args(["--keychain-profile", keychain_profile])
+            .arg("--wait")
+            .successful_output()
+            .context("Notarization failed")?;
+
+        if path.is_dir() {
+            // The ticket can only be stapled to the bundle itself, not the
+            // zip; consumers re-zipping the bundle keep the ticket.
+            crate::utils::xcrun_tool("stapler")
+                .args(["staple", path.as_str()])
+                .successful_output()
+                .context("Can't staple the notarization ticket")?;
+            std::fs::remove_file(&submission)
+                .with_context(|| format!("Can't remove {submission}"))?;
+        }
+
+        println!("Notarized {path}");
+        Ok(())
+    };
+    run().map_err(crate::Error::from)
+}
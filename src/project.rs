@@ -0,0 +1,1097 @@
+use std::collections::BTreeMap;
+use std::env;
+
+use anyhow::{bail, Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use cargo_metadata::{Metadata, MetadataCommand, Package};
+
+use crate::error::Error;
+
+/// The Cargo workspace the helper operates on, plus the UniFFI-specific
+/// configuration read from each package's `uniffi.toml`.
+pub(crate) struct Project {
+    pub(crate) metadata: Metadata,
+    /// Root directory for everything the helper writes; see [`Self::output_root`].
+    output_root: Utf8PathBuf,
+    /// Name of the merged FFI clang module, shared by all UniFFI packages.
+    pub(crate) ffi_module_name: String,
+    /// Project-supplied module map template, overriding the embedded one.
+    pub(crate) modulemap_template: Option<Utf8PathBuf>,
+    /// How the generated module map groups the headers. From
+    /// `modulemap_layout` in `uniffi.toml`; defaults to one flat header list.
+    pub(crate) modulemap_layout: ModulemapLayout,
+    /// Whether the top-level module ends with `export *` (default true).
+    /// Disabled via `modulemap_export_all = false` for consumers who want
+    /// imports resolved strictly through the submodules.
+    pub(crate) modulemap_export_all: bool,
+    /// System libraries the Rust code links against (e.g. `sqlite3`). The
+    /// union of every package's `link_libraries` arrays; emitted as `link`
+    /// directives in the module map and `linkerSettings` in the manifest.
+    pub(crate) link_libraries: Vec<String>,
+    /// Apple frameworks required at runtime (e.g. `Security`), declared via
+    /// `link_frameworks` and emitted alongside [`Self::link_libraries`].
+    pub(crate) link_frameworks: Vec<String>,
+    /// Prebuilt static archives merged into each slice during packaging,
+    /// keyed by platform name (`ios`, `macos`, …) or full target triple. From
+    /// the `[extra_archives]` tables in `uniffi.toml`; relative paths are
+    /// resolved against the declaring package.
+    pub(crate) extra_archives: BTreeMap<String, Vec<Utf8PathBuf>>,
+    /// Extra environment variables for cargo builds, keyed by platform name
+    /// (`ios`, `macos`, …) or full target triple. From the `[build_env]`
+    /// tables in `uniffi.toml`; `RUSTFLAGS` entries are appended rather than
+    /// replacing the ambient value.
+    pub(crate) build_env: BTreeMap<String, BTreeMap<String, String>>,
+    /// Cargo profile settings per platform name or target triple, from the
+    /// `[profile_overrides]` tables in `uniffi.toml`. Injected as
+    /// `--config profile.<p>.<key>=<value>` so e.g. watchOS can build with
+    /// `opt-level = "z"` and fat LTO while the other slices keep the
+    /// profile's defaults.
+    pub(crate) profile_overrides: BTreeMap<String, BTreeMap<String, toml::Value>>,
+    /// Whether builds inject `profile.<p>.panic="abort"` (default true).
+    /// Disabled via `panic_abort = false` for code that must unwind, e.g.
+    /// through C callbacks.
+    pub(crate) panic_abort: bool,
+    /// Whether builds inject `profile.<p>.debug=true` (default true) so
+    /// release builds keep debug info for dSYM extraction. Disabled via
+    /// `force_debug_info = false`.
+    pub(crate) force_debug_info: bool,
+    /// `split_debuginfo` in `uniffi.toml`: injects
+    /// `profile.<p>.split-debuginfo` so debug info placement (`packed`,
+    /// `unpacked`, or `off`) can be tuned against dSYM extraction, debugger
+    /// experience, and archive size. Unset leaves rustc's platform default,
+    /// which embeds everything in the archives.
+    pub(crate) split_debuginfo: Option<String>,
+    /// `// swift-tools-version` for the generated `Package.swift`. From
+    /// `swift_tools_version` in `uniffi.toml`; defaults to 5.10.
+    pub(crate) swift_tools_version: String,
+    /// Swift language mode declared in the generated manifest, when set via
+    /// `swift_language_version` in `uniffi.toml`.
+    pub(crate) swift_language_version: Option<String>,
+    /// Executables run after binding generation, each invoked with the path
+    /// to a JSON description of the generated files. From the
+    /// `post_generation_plugins` array in `uniffi.toml`; relative paths are
+    /// resolved against the declaring package.
+    pub(crate) post_generation_plugins: Vec<Utf8PathBuf>,
+    /// Extra entry names skipped when vendoring Swift source trees, on top
+    /// of the built-in `.git`/`.build`/`.swiftpm`. From `vendor_excludes` in
+    /// `uniffi.toml`.
+    pub(crate) vendor_excludes: Vec<String>,
+    /// Per-target compiler settings for the generated manifest, keyed by
+    /// target name. From `[swift_settings.<TargetName>]` in `uniffi.toml`;
+    /// lets e.g. the bindings target stay in Swift 5 mode while the public
+    /// wrapper adopts Swift 6.
+    pub(crate) swift_settings: BTreeMap<String, SwiftSettings>,
+    /// Access level the wrapper post-processing gives the generated bindings
+    /// declarations: `public` (the default), or Swift 5.9's `package` to hide
+    /// the internal bindings targets from consumers while sibling targets in
+    /// the generated package still see them. SPM passes `-package-name`
+    /// within a package automatically, so no extra compiler settings are
+    /// needed. From `bindings_access_level` in `uniffi.toml`.
+    pub(crate) bindings_access_level: String,
+    /// SPM products for the generated manifest, keyed by product name, each
+    /// listing the targets it exposes. From the `[products]` table in
+    /// `uniffi.toml` (e.g. `WordPressKit = ["WordPressAPI", "WordPressCore"]`);
+    /// empty means one product per public module.
+    pub(crate) products: BTreeMap<String, Vec<String>>,
+    /// Download URL for released XCFramework archives, with `{version}` and
+    /// `{name}` placeholders. From `release_url_template` in `uniffi.toml`;
+    /// required by the `release` subcommand.
+    pub(crate) release_url_template: Option<String>,
+    pub(crate) uniffi_packages: Vec<UniffiPackage>,
+}
+
+/// A workspace member (or path/git dependency) that exposes a UniFFI API.
+pub(crate) struct UniffiPackage {
+    pub(crate) package: Package,
+    /// Swift module name of the generated bindings target.
+    pub(crate) internal_module_name: String,
+    /// Swift module name of the hand-written public wrapper target.
+    pub(crate) public_module_name: String,
+    /// UniFFI external types this package uses, and the Swift module each one
+    /// lives in.
+    pub(crate) external_types: Vec<ExternalType>,
+    /// Extra Swift modules the package's generated wrappers import (e.g.
+    /// `Combine` when a post-generation plugin adds publishers). From
+    /// `extra_imports` in `uniffi.toml`.
+    pub(crate) extra_imports: Vec<String>,
+    /// Dependency overrides for the package's Swift source targets, keyed by
+    /// target name. From `[swift_target_dependencies]` in `uniffi.toml`; the
+    /// generated bindings module is always added on top.
+    pub(crate) swift_target_dependencies: BTreeMap<String, Vec<String>>,
+}
+
+/// Header grouping of the generated `module.modulemap`.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum ModulemapLayout {
+    /// Every header listed directly in the FFI module.
+    Flat,
+    /// One submodule per UniFFI crate holding that crate's headers, so
+    /// Objective-C consumers can `#include` them individually.
+    PerCrate,
+}
+
+impl ModulemapLayout {
+    fn parse(value: &str, path: &Utf8Path) -> Result<Self> {
+        match value {
+            "flat" => Ok(Self::Flat),
+            "per-crate" => Ok(Self::PerCrate),
+            other => bail!("Unsupported modulemap_layout {other} in {path}; expected flat or per-crate"),
+        }
+    }
+}
+
+/// Compiler settings for one generated SPM target, rendered as its
+/// `swiftSettings` array.
+#[derive(Clone)]
+pub(crate) struct SwiftSettings {
+    /// Language mode for this target only, overriding the package default.
+    pub(crate) language_version: Option<String>,
+    /// Compilation conditions, rendered as `.define(…)` entries.
+    pub(crate) defines: Vec<String>,
+}
+
+/// A UniFFI `uniffi(external)` type defined in another crate's Swift module.
+///
+/// Declared in `uniffi.toml` as `[external_types] TypeName = "SwiftModule"`.
+/// The generated wrapper prefix imports the module and adds a typealias so
+/// the generated bindings resolve the type by its bare name.
+pub(crate) struct ExternalType {
+    pub(crate) name: String,
+    pub(crate) module: String,
+}
+
+impl Project {
+    pub(crate) fn from_current_dir() -> Result<Self> {
+        let mut metadata_command = MetadataCommand::new();
+        let mut other_options: Vec<String> = Vec::new();
+        if crate::utils::offline() {
+            other_options.push("--offline".to_string());
+        }
+        other_options.extend(
+            crate::utils::cargo_lock_flags()
+                .into_iter()
+                .map(str::to_string),
+        );
+        if !other_options.is_empty() {
+            metadata_command.other_options(other_options);
+        }
+        let metadata = metadata_command
+            .exec()
+            .context("Can't read cargo metadata")?;
+
+        let current_dir = Utf8PathBuf::try_from(env::current_dir()?)?;
+        if current_dir != metadata.workspace_root {
+            return Err(Error::NotWorkspaceRoot {
+                workspace_root: metadata.workspace_root.clone(),
+            }
+            .into());
+        }
+
+        let mut ffi_module_name: Option<String> = None;
+        let mut modulemap_template: Option<Utf8PathBuf> = None;
+        let mut modulemap_layout: Option<ModulemapLayout> = None;
+        let mut modulemap_export_all: Option<bool> = None;
+        let mut panic_abort: Option<bool> = None;
+        let mut force_debug_info: Option<bool> = None;
+        let mut split_debuginfo: Option<String> = None;
+        let mut swift_tools_version: Option<String> = None;
+        let mut output_root: Option<Utf8PathBuf> = None;
+        let mut swift_language_version: Option<String> = None;
+        let mut swift_settings: BTreeMap<String, SwiftSettings> = BTreeMap::new();
+        let mut release_url_template: Option<String> = None;
+        let mut vendor_excludes: Option<Vec<String>> = None;
+        let mut post_generation_plugins: Option<Vec<Utf8PathBuf>> = None;
+        let mut link_libraries: Vec<String> = Vec::new();
+        let mut link_frameworks: Vec<String> = Vec::new();
+        let mut build_env: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+        let mut profile_overrides: BTreeMap<String, BTreeMap<String, toml::Value>> =
+            BTreeMap::new();
+        let mut extra_archives: BTreeMap<String, Vec<Utf8PathBuf>> = BTreeMap::new();
+        let mut products: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        let mut bindings_access_level: Option<String> = None;
+        let mut uniffi_packages = Vec::new();
+        for package in metadata.packages.iter().filter(|p| is_uniffi_package(p)) {
+            let config = UniffiConfig::read(package)?;
+            if let Some(template) = &config.modulemap_template {
+                // Relative paths are resolved against the declaring package.
+                let manifest_dir = package
+                    .manifest_path
+                    .parent()
+                    .expect("manifest path always has a parent");
+                modulemap_template.get_or_insert(manifest_dir.join(template));
+            }
+            if let Some(layout) = config.modulemap_layout {
+                modulemap_layout.get_or_insert(layout);
+            }
+            if let Some(value) = config.modulemap_export_all {
+                modulemap_export_all.get_or_insert(value);
+            }
+            for (section, vars) in &config.build_env {
+                build_env.entry(section.clone()).or_insert_with(|| vars.clone());
+            }
+            for (section, settings) in &config.profile_overrides {
+                profile_overrides
+                    .entry(section.clone())
+                    .or_insert_with(|| settings.clone());
+            }
+            for (section, archives) in &config.extra_archives {
+                let manifest_dir = package
+                    .manifest_path
+                    .parent()
+                    .expect("manifest path always has a parent");
+                extra_archives.entry(section.clone()).or_insert_with(|| {
+                    archives
+                        .iter()
+                        .map(|archive| manifest_dir.join(archive))
+                        .collect()
+                });
+            }
+            if let Some(value) = config.panic_abort {
+                panic_abort.get_or_insert(value);
+            }
+            if let Some(value) = config.force_debug_info {
+                force_debug_info.get_or_insert(value);
+            }
+            if let Some(value) = &config.split_debuginfo {
+                split_debuginfo.get_or_insert(value.clone());
+            }
+            if let Some(value) = &config.swift_tools_version {
+                swift_tools_version.get_or_insert(value.clone());
+            }
+            if let Some(value) = &config.swift_language_version {
+                swift_language_version.get_or_insert(value.clone());
+            }
+            if let Some(value) = &config.release_url_template {
+                release_url_template.get_or_insert(value.clone());
+            }
+            if let Some(root) = &config.output_root {
+                // Relative roots are anchored at the workspace root, not the
+                // declaring package, so every package agrees on the location.
+                output_root.get_or_insert(metadata.workspace_root.join(root));
+            }
+            if let Some(excludes) = &config.vendor_excludes {
+                vendor_excludes.get_or_insert(excludes.clone());
+            }
+            if let Some(plugins) = &config.post_generation_plugins {
+                let manifest_dir = package
+                    .manifest_path
+                    .parent()
+                    .expect("manifest path always has a parent");
+                post_generation_plugins.get_or_insert_with(|| {
+                    plugins.iter().map(|plugin| manifest_dir.join(plugin)).collect()
+                });
+            }
+            // Unlike the single-value keys these are unioned: each crate
+            // declares its own system dependencies.
+            if let Some(libraries) = &config.link_libraries {
+                link_libraries.extend(libraries.iter().cloned());
+            }
+            if let Some(frameworks) = &config.link_frameworks {
+                link_frameworks.extend(frameworks.iter().cloned());
+            }
+            for (target, settings) in &config.swift_settings {
+                swift_settings
+                    .entry(target.clone())
+                    .or_insert_with(|| settings.clone());
+            }
+            for (product, targets) in &config.products {
+                products
+                    .entry(product.clone())
+                    .or_insert_with(|| targets.clone());
+            }
+            if let Some(level) = &config.bindings_access_level {
+                bindings_access_level.get_or_insert(level.clone());
+            }
+            if let Some(name) = &config.ffi_module_name {
+                match &ffi_module_name {
+                    None => ffi_module_name = Some(name.clone()),
+                    Some(existing) if existing != name => bail!(
+                        "Conflicting ffi_module_name values: {existing} and {name} (from {})",
+                        package.name
+                    ),
+                    Some(_) => {}
+                }
+            }
+            uniffi_packages.push(UniffiPackage {
+                internal_module_name: config.internal_module_name(package),
+                public_module_name: config.spm_public_module_name,
+                external_types: config.external_types,
+                extra_imports: config.extra_imports.unwrap_or_default(),
+                swift_target_dependencies: config.swift_target_dependencies,
+                package: package.clone(),
+            });
+        }
+
+        if uniffi_packages.is_empty() {
+            return Err(Error::NoUniffiPackages.into());
+        }
+        // Without an explicit name anywhere, derive one from the first crate.
+        let ffi_module_name = ffi_module_name.unwrap_or_else(|| {
+            format!("{}FFI", pascal_case(&uniffi_packages[0].package.name))
+        });
+        link_libraries.sort();
+        link_libraries.dedup();
+        link_frameworks.sort();
+        link_frameworks.dedup();
+
+        Ok(Self {
+            output_root: output_root.unwrap_or_else(|| metadata.target_directory.clone()),
+            metadata,
+            ffi_module_name,
+            modulemap_template,
+            modulemap_layout: modulemap_layout.unwrap_or(ModulemapLayout::Flat),
+            modulemap_export_all: modulemap_export_all.unwrap_or(true),
+            build_env,
+            profile_overrides,
+            panic_abort: panic_abort.unwrap_or(true),
+            force_debug_info: force_debug_info.unwrap_or(true),
+            split_debuginfo,
+            swift_tools_version: swift_tools_version.unwrap_or_else(|| "5.10".to_string()),
+            swift_language_version,
+            swift_settings,
+            products,
+            bindings_access_level: bindings_access_level.unwrap_or_else(|| "public".to_string()),
+            release_url_template,
+            vendor_excludes: vendor_excludes.unwrap_or_default(),
+            post_generation_plugins: post_generation_plugins.unwrap_or_default(),
+            link_libraries,
+            link_frameworks,
+            extra_archives,
+            uniffi_packages,
+        })
+    }
+
+    /// Restrict the UniFFI packages to `names`, e.g. from repeated
+    /// `--package` flags. An empty list keeps everything. Cargo still builds
+    /// each selected package's dependencies as usual.
+    pub(crate) fn select_packages(&mut self, names: &[String]) -> Result<()> {
+        if names.is_empty() {
+            return Ok(());
+        }
+        for name in names {
+            if !self
+                .uniffi_packages
+                .iter()
+                .any(|p| p.package.name.as_str() == name)
+            {
+                bail!("No UniFFI package named `{name}` in the workspace");
+            }
+        }
+        self.uniffi_packages
+            .retain(|p| names.iter().any(|n| n == p.package.name.as_str()));
+        Ok(())
+    }
+
+    pub(crate) fn workspace_root(&self) -> &Utf8Path {
+        &self.metadata.workspace_root
+    }
+
+    /// Cargo's own output directory. Compiled libraries and the generated
+    /// bindings intermediates live here regardless of [`Self::output_root`],
+    /// because their layout is dictated by cargo.
+    pub(crate) fn target_dir(&self) -> &Utf8Path {
+        &self.metadata.target_directory
+    }
+
+    /// Root directory for every artifact the helper itself produces. Defaults
+    /// to `target/`; configurable via `output_root` in `uniffi.toml`, with
+    /// relative paths resolved against the workspace root. Layout:
+    ///
+    /// - `<Module>.xcframework` — assembled frameworks, one per module
+    /// - `<ffi_module_name>/swift-wrapper/` — post-processed bindings sources
+    /// - `<ffi_module_name>/dsyms/`, `wrapper-archives/`, `umbrella/`
+    /// - `swift-vendored/` — vendored dependency Swift sources
+    /// - `tmp/` — per-invocation scratch space
+    pub(crate) fn output_root(&self) -> &Utf8Path {
+        &self.output_root
+    }
+
+    /// A scratch directory under the output root's `tmp/`, unique per
+    /// invocation so concurrent builds sharing a workspace don't trample each
+    /// other.
+    pub(crate) fn tmp_dir(&self, label: &str) -> Utf8PathBuf {
+        self.output_root()
+            .join("tmp")
+            .join(format!("{label}-{}", std::process::id()))
+    }
+
+    /// Directory holding the post-processed UniFFI-generated Swift sources,
+    /// one subdirectory per internal module.
+    pub(crate) fn swift_wrapper_dir(&self) -> Utf8PathBuf {
+        self.output_root()
+            .join(&self.ffi_module_name)
+            .join("swift-wrapper")
+    }
+
+    /// Directory holding the extracted `.dSYM` bundles.
+    pub(crate) fn dsyms_dir(&self) -> Utf8PathBuf {
+        self.output_root().join(&self.ffi_module_name).join("dsyms")
+    }
+
+    /// Directory holding the per-platform wrapper framework archives.
+    pub(crate) fn wrapper_archives_dir(&self) -> Utf8PathBuf {
+        self.output_root()
+            .join(&self.ffi_module_name)
+            .join("wrapper-archives")
+    }
+
+    /// Directory holding the generated umbrella target's sources.
+    pub(crate) fn umbrella_dir(&self) -> Utf8PathBuf {
+        self.output_root()
+            .join(&self.ffi_module_name)
+            .join("umbrella")
+    }
+
+    /// Directory holding the generated smoke-test target's sources.
+    pub(crate) fn smoke_tests_dir(&self) -> Utf8PathBuf {
+        self.output_root()
+            .join(&self.ffi_module_name)
+            .join("smoke-tests")
+    }
+
+    /// Directory holding vendored copies of out-of-workspace packages' Swift
+    /// sources, one subdirectory per package.
+    pub(crate) fn vendored_sources_dir(&self) -> Utf8PathBuf {
+        self.output_root().join("swift-vendored")
+    }
+
+    /// Location of the assembled XCFramework.
+    pub(crate) fn xcframework_path(&self) -> Utf8PathBuf {
+        self.output_root()
+            .join(format!("{}.xcframework", self.ffi_module_name))
+    }
+
+    /// Location of a linkage-variant XCFramework (`static` or `dynamic`).
+    pub(crate) fn variant_xcframework_path(&self, variant: &str) -> Utf8PathBuf {
+        self.output_root()
+            .join(format!("{}-{variant}.xcframework", self.ffi_module_name))
+    }
+
+    /// Location of one crate's XCFramework in the per-crate layout.
+    pub(crate) fn crate_xcframework_path(&self, package: &UniffiPackage) -> Utf8PathBuf {
+        self.output_root()
+            .join(format!("{}.xcframework", package.ffi_module_name()))
+    }
+
+    /// Location of one module's XCFramework by name, e.g. a wrapper module's.
+    pub(crate) fn module_xcframework_path(&self, module: &str) -> Utf8PathBuf {
+        self.output_root().join(format!("{module}.xcframework"))
+    }
+
+    /// Location of the single-platform `.framework` bundle.
+    pub(crate) fn framework_path(&self, name: &str) -> Utf8PathBuf {
+        self.output_root().join(format!("{name}.framework"))
+    }
+}
+
+/// Print the fully resolved configuration: workspace paths, the detected
+/// UniFFI packages with their module names, the platform/target matrix, and
+/// every project-wide setting with the `uniffi.toml` that supplied it.
+/// Settings merge first-set-wins across packages, which makes surprises easy;
+/// this shows the merge result without reverse-engineering it from behavior.
+pub fn show_config(deployment_targets_from: Option<&Utf8Path>) -> crate::Result<()> {
+    let run = || -> Result<()> {
+        let project = Project::from_current_dir()?;
+        // Re-read each package's uniffi.toml to attribute every merged value
+        // to the file that set it (the first one wins, matching
+        // `from_current_dir`).
+        let configs: Vec<(&str, UniffiConfig)> = project
+            .uniffi_packages
+            .iter()
+            .map(|p| Ok((p.package.name.as_str(), UniffiConfig::read(&p.package)?)))
+            .collect::<Result<_>>()?;
+        let source = |set_in: &dyn Fn(&UniffiConfig) -> bool| -> String {
+            configs
+                .iter()
+                .find(|(_, config)| set_in(config))
+                .map(|(name, _)| format!("{name}/uniffi.toml"))
+                .unwrap_or_else(|| "default".to_string())
+        };
+
+        println!("workspace root:      {}", project.workspace_root());
+        println!("target dir:          {}", project.target_dir());
+        println!(
+            "output root:         {} ({})",
+            project.output_root(),
+            source(&|c| c.output_root.is_some())
+        );
+        println!(
+            "ffi module name:     {} ({})",
+            project.ffi_module_name,
+            source(&|c| c.ffi_module_name.is_some())
+        );
+        println!("xcframework:         {}", project.xcframework_path());
+        println!("swift wrappers:      {}", project.swift_wrapper_dir());
+        println!(
+            "modulemap layout:    {} ({})",
+            match project.modulemap_layout {
+                ModulemapLayout::Flat => "flat",
+                ModulemapLayout::PerCrate => "per-crate",
+            },
+            source(&|c| c.modulemap_layout.is_some())
+        );
+        println!(
+            "modulemap export *:  {} ({})",
+            project.modulemap_export_all,
+            source(&|c| c.modulemap_export_all.is_some())
+        );
+        println!(
+            "panic abort:         {} ({})",
+            project.panic_abort,
+            source(&|c| c.panic_abort.is_some())
+        );
+        println!(
+            "force debug info:    {} ({})",
+            project.force_debug_info,
+            source(&|c| c.force_debug_info.is_some())
+        );
+        println!(
+            "split debuginfo:     {} ({})",
+            project.split_debuginfo.as_deref().unwrap_or("default"),
+            source(&|c| c.split_debuginfo.is_some())
+        );
+        println!(
+            "swift tools version: {} ({})",
+            project.swift_tools_version,
+            source(&|c| c.swift_tools_version.is_some())
+        );
+        println!(
+            "bindings access:     {} ({})",
+            project.bindings_access_level,
+            source(&|c| c.bindings_access_level.is_some())
+        );
+        if let Some(version) = &project.swift_language_version {
+            println!(
+                "swift language:      {version} ({})",
+                source(&|c| c.swift_language_version.is_some())
+            );
+        }
+        if !project.link_libraries.is_empty() {
+            println!("link libraries:      {}", project.link_libraries.join(", "));
+        }
+        if !project.link_frameworks.is_empty() {
+            println!("link frameworks:     {}", project.link_frameworks.join(", "));
+        }
+        if let Some(template) = &project.release_url_template {
+            println!(
+                "release url:         {template} ({})",
+                source(&|c| c.release_url_template.is_some())
+            );
+        }
+
+        println!("\npackages:");
+        for package in &project.uniffi_packages {
+            let origin = if package.is_in_workspace(project.workspace_root()) {
+                "workspace".to_string()
+            } else if let Some(revision) = package.git_revision() {
+                format!("git {revision}")
+            } else {
+                "path/registry".to_string()
+            };
+            println!(
+                "  {} ({origin}): bindings module {}, public module {}",
+                package.package.name, package.internal_module_name, package.public_module_name
+            );
+        }
+
+        println!("\nplatforms:");
+        for platform in crate::xcframework::ApplePlatform::all() {
+            println!(
+                "  {}: {}",
+                platform.name(),
+                platform.target_triples().join(", ")
+            );
+        }
+
+        if let Some(path) = deployment_targets_from {
+            let targets = crate::deployment::DeploymentTargets::from_path(path)?;
+            println!("\ndeployment targets (from {path}):");
+            for platform in targets.spm_platforms() {
+                println!("  {platform}");
+            }
+        }
+        Ok(())
+    };
+    run().map_err(crate::Error::from)
+}
+
+impl UniffiPackage {
+    pub(crate) fn manifest_dir(&self) -> &Utf8Path {
+        self.package
+            .manifest_path
+            .parent()
+            .expect("manifest path always has a parent")
+    }
+
+    /// Clang module name of this package's own FFI module in the per-crate
+    /// layout. Suffixed so it can't collide with the bindings target.
+    pub(crate) fn ffi_module_name(&self) -> String {
+        format!("{}FFI", self.internal_module_name)
+    }
+
+    /// The resolved commit hash when this package comes in as a `git+`
+    /// dependency; `None` for workspace members and registry crates.
+    pub(crate) fn git_revision(&self) -> Option<&str> {
+        let source = self.package.source.as_ref()?;
+        if !source.repr.starts_with("git+") {
+            return None;
+        }
+        source.repr.rsplit_once('#').map(|(_, rev)| rev)
+    }
+
+    /// File name of the static library cargo produces for this package.
+    pub(crate) fn library_file_name(&self) -> String {
+        format!("lib{}.a", self.package.name.replace('-', "_"))
+    }
+
+    /// The package's Swift sources: `native/swift` next to its `Cargo.toml`.
+    pub(crate) fn swift_source_dir(&self) -> Utf8PathBuf {
+        self.manifest_dir().join("native").join("swift")
+    }
+
+    /// Whether this package lives inside the workspace directory. Packages
+    /// pulled in as path/git dependencies need their Swift sources vendored
+    /// into `target/` so `Package.swift` can reference them.
+    pub(crate) fn is_in_workspace(&self, workspace_root: &Utf8Path) -> bool {
+        self.manifest_dir().starts_with(workspace_root)
+    }
+}
+
+fn is_uniffi_package(package: &Package) -> bool {
+    package.dependencies.iter().any(|d| d.name == "uniffi")
+        && package
+            .manifest_path
+            .parent()
+            .is_some_and(|dir| dir.join("uniffi.toml").exists())
+}
+
+/// The keys we read from a package's `uniffi.toml`.
+struct UniffiConfig {
+    /// Name of the merged FFI module. Optional: without one anywhere, the
+    /// first UniFFI crate's name decides.
+    ffi_module_name: Option<String>,
+    spm_public_module_name: String,
+    bindings_module_name: Option<String>,
+    /// Path to a custom module.modulemap template, relative to the package.
+    modulemap_template: Option<String>,
+    modulemap_layout: Option<ModulemapLayout>,
+    modulemap_export_all: Option<bool>,
+    external_types: Vec<ExternalType>,
+    extra_imports: Option<Vec<String>>,
+    swift_target_dependencies: BTreeMap<String, Vec<String>>,
+    build_env: BTreeMap<String, BTreeMap<String, String>>,
+    profile_overrides: BTreeMap<String, BTreeMap<String, toml::Value>>,
+    panic_abort: Option<bool>,
+    force_debug_info: Option<bool>,
+    split_debuginfo: Option<String>,
+    swift_tools_version: Option<String>,
+    swift_language_version: Option<String>,
+    swift_settings: BTreeMap<String, SwiftSettings>,
+    /// Product names mapped to the targets each one exposes.
+    products: BTreeMap<String, Vec<String>>,
+    bindings_access_level: Option<String>,
+    /// Download URL template for released archives.
+    release_url_template: Option<String>,
+    vendor_excludes: Option<Vec<String>>,
+    post_generation_plugins: Option<Vec<String>>,
+    link_libraries: Option<Vec<String>>,
+    link_frameworks: Option<Vec<String>>,
+    /// Prebuilt archive paths per platform or triple, relative to the package.
+    extra_archives: BTreeMap<String, Vec<String>>,
+    /// Output root for generated artifacts, relative to the workspace root.
+    output_root: Option<Utf8PathBuf>,
+}
+
+/// Tools versions the generated manifest is known to be valid under. Older
+/// Xcodes reject manifests declaring a newer tools version, so this is
+/// consumer-facing configuration, not just syntax.
+const SWIFT_TOOLS_VERSIONS: &[&str] = &["5.9", "5.10", "6.0"];
+
+const SWIFT_LANGUAGE_VERSIONS: &[&str] = &["5", "6"];
+
+/// The `-C split-debuginfo` modes rustc accepts on Apple targets.
+const SPLIT_DEBUGINFO_MODES: &[&str] = &["packed", "unpacked", "off"];
+
+/// Access levels the bindings post-processing can rewrite declarations to.
+const BINDINGS_ACCESS_LEVELS: &[&str] = &["public", "package"];
+
+impl UniffiConfig {
+    fn read(package: &Package) -> Result<Self> {
+        let path = package
+            .manifest_path
+            .parent()
+            .expect("manifest path always has a parent")
+            .join("uniffi.toml");
+        let contents =
+            std::fs::read_to_string(&path).with_context(|| format!("Can't read {path}"))?;
+        let table: toml::Table = contents
+            .parse()
+            .with_context(|| format!("Can't parse {path}"))?;
+
+        let optional_string = |key: &str| -> Option<String> {
+            table.get(key).and_then(|v| v.as_str()).map(str::to_string)
+        };
+
+        Ok(Self {
+            ffi_module_name: optional_string("ffi_module_name"),
+            // `wp_spm_public_module_name` is the historical spelling; without
+            // either key the PascalCase crate name serves as the default.
+            spm_public_module_name: optional_string("spm_public_module_name")
+                .or_else(|| optional_string("wp_spm_public_module_name"))
+                .unwrap_or_else(|| pascal_case(&package.name)),
+            modulemap_template: table
+                .get("modulemap_template")
+                .and_then(|v| v.as_str())
+                .map(str::to_string),
+            modulemap_layout: table
+                .get("modulemap_layout")
+                .and_then(|v| v.as_str())
+                .map(|value| ModulemapLayout::parse(value, &path))
+                .transpose()?,
+            modulemap_export_all: table.get("modulemap_export_all").and_then(|v| v.as_bool()),
+            bindings_module_name: table
+                .get("bindings")
+                .and_then(|b| b.get("swift"))
+                .and_then(|s| s.get("module_name"))
+                .and_then(|v| v.as_str())
+                .map(str::to_string),
+            external_types: external_types(&table, &path)?,
+            extra_imports: string_array(&table, &path, "extra_imports")?,
+            swift_target_dependencies: swift_target_dependencies(&table, &path)?,
+            build_env: build_env(&table, &path)?,
+            profile_overrides: profile_overrides(&table, &path)?,
+            panic_abort: table.get("panic_abort").and_then(|v| v.as_bool()),
+            force_debug_info: table.get("force_debug_info").and_then(|v| v.as_bool()),
+            split_debuginfo: validated_choice(
+                &table,
+                &path,
+                "split_debuginfo",
+                SPLIT_DEBUGINFO_MODES,
+            )?,
+            swift_tools_version: validated_choice(
+                &table,
+                &path,
+                "swift_tools_version",
+                SWIFT_TOOLS_VERSIONS,
+            )?,
+            swift_language_version: validated_choice(
+                &table,
+                &path,
+                "swift_language_version",
+                SWIFT_LANGUAGE_VERSIONS,
+            )?,
+            swift_settings: swift_settings(&table, &path)?,
+            products: products(&table, &path)?,
+            bindings_access_level: validated_choice(
+                &table,
+                &path,
+                "bindings_access_level",
+                BINDINGS_ACCESS_LEVELS,
+            )?,
+            release_url_template: optional_string("release_url_template"),
+            vendor_excludes: string_array(&table, &path, "vendor_excludes")?,
+            post_generation_plugins: string_array(&table, &path, "post_generation_plugins")?,
+            link_libraries: string_array(&table, &path, "link_libraries")?,
+            link_frameworks: string_array(&table, &path, "link_frameworks")?,
+            extra_archives: extra_archives(&table, &path)?,
+            output_root: table
+                .get("output_root")
+                .and_then(|v| v.as_str())
+                .map(Utf8PathBuf::from),
+        })
+    }
+
+    /// `[bindings.swift] module_name` if set, which is what uniffi-bindgen
+    /// itself uses; otherwise uniffi's default of the crate name.
+    fn internal_module_name(&self, package: &Package) -> String {
+        self.bindings_module_name
+            .clone()
+            .unwrap_or_else(|| package.name.replace('-', "_"))
+    }
+}
+
+/// Parse the `[extra_archives]` tables: per-platform (or per-triple) arrays
+/// of prebuilt archive paths.
+fn extra_archives(
+    table: &toml::Table,
+    path: &Utf8Path,
+) -> Result<BTreeMap<String, Vec<String>>> {
+    let Some(value) = table.get("extra_archives") else {
+        return Ok(BTreeMap::new());
+    };
+    let Some(sections) = value.as_table() else {
+        bail!("[extra_archives] in {path} must contain per-platform arrays");
+    };
+    let mut archives = BTreeMap::new();
+    for (section, paths) in sections {
+        let Some(paths) = paths.as_array() else {
+            bail!("extra_archives.{section} in {path} must be an array of paths");
+        };
+        let mut parsed = Vec::new();
+        for value in paths {
+            let Some(value) = value.as_str() else {
+                bail!("extra_archives.{section} in {path} must contain strings");
+            };
+            parsed.push(value.to_string());
+        }
+        archives.insert(section.clone(), parsed);
+    }
+    Ok(archives)
+}
+
+/// Parse the `[products]` table: product names mapped to arrays of target
+/// names, decoupling what consumers depend on from how the targets are laid
+/// out (e.g. one `WordPressKit` product exposing two wrapper targets).
+fn products(table: &toml::Table, path: &Utf8Path) -> Result<BTreeMap<String, Vec<String>>> {
+    let Some(value) = table.get("products") else {
+        return Ok(BTreeMap::new());
+    };
+    let Some(sections) = value.as_table() else {
+        bail!("[products] in {path} must map product names to arrays of targets");
+    };
+    let mut products = BTreeMap::new();
+    for (product, targets) in sections {
+        let Some(targets) = targets.as_array() else {
+            bail!("products.{product} in {path} must be an array of target names");
+        };
+        let mut parsed = Vec::new();
+        for value in targets {
+            let Some(value) = value.as_str() else {
+                bail!("products.{product} in {path} must contain strings");
+            };
+            parsed.push(value.to_string());
+        }
+        products.insert(product.clone(), parsed);
+    }
+    Ok(products)
+}
+
+/// Read an optional array-of-strings key.
+fn string_array(table: &toml::Table, path: &Utf8Path, key: &str) -> Result<Option<Vec<String>>> {
+    let Some(value) = table.get(key) else {
+        return Ok(None);
+    };
+    let Some(values) = value.as_array() else {
+        bail!("{key} in {path} must be an array of strings");
+    };
+    let mut strings = Vec::new();
+    for value in values {
+        let Some(value) = value.as_str() else {
+            bail!("{key} in {path} must contain strings");
+        };
+        strings.push(value.to_string());
+    }
+    Ok(Some(strings))
+}
+
+/// Read an optional string key and check it against the allowed set, so a
+/// typo fails at configuration time with the valid options listed.
+fn validated_choice(
+    table: &toml::Table,
+    path: &Utf8Path,
+    key: &str,
+    allowed: &[&str],
+) -> Result<Option<String>> {
+    let Some(value) = table.get(key) else {
+        return Ok(None);
+    };
+    let Some(value) = value.as_str() else {
+        bail!("{key} in {path} must be a string");
+    };
+    if !allowed.contains(&value) {
+        bail!(
+            "{key} in {path} must be one of {}, got `{value}`",
+            allowed.join(", ")
+        );
+    }
+    Ok(Some(value.to_string()))
+}
+
+/// Parse the `[profile_overrides.<platform-or-triple>]` tables: cargo profile
+/// settings (`opt-level`, `lto`, `codegen-units`, …) applied to builds for
+/// that platform or target. Values stay as TOML scalars, since `lto` can be a
+/// string or a bool and `codegen-units` is an integer.
+fn profile_overrides(
+    table: &toml::Table,
+    path: &Utf8Path,
+) -> Result<BTreeMap<String, BTreeMap<String, toml::Value>>> {
+    let Some(value) = table.get("profile_overrides") else {
+        return Ok(BTreeMap::new());
+    };
+    let Some(sections) = value.as_table() else {
+        bail!("[profile_overrides] in {path} must contain per-platform tables");
+    };
+    let mut overrides = BTreeMap::new();
+    for (section, settings) in sections {
+        let Some(settings) = settings.as_table() else {
+            bail!("profile_overrides.{section} in {path} must be a table of profile settings");
+        };
+        let mut parsed = BTreeMap::new();
+        for (key, value) in settings {
+            if value.is_table() || value.is_array() {
+                bail!(
+                    "profile_overrides.{section}.{key} in {path} must be a \
+                     string, number, or boolean"
+                );
+            }
+            parsed.insert(key.clone(), value.clone());
+        }
+        overrides.insert(section.clone(), parsed);
+    }
+    Ok(overrides)
+}
+
+/// Parse the `[build_env.<platform-or-triple>]` tables: environment variables
+/// applied to cargo builds for that platform or target.
+fn build_env(
+    table: &toml::Table,
+    path: &Utf8Path,
+) -> Result<BTreeMap<String, BTreeMap<String, String>>> {
+    let Some(value) = table.get("build_env") else {
+        return Ok(BTreeMap::new());
+    };
+    let Some(sections) = value.as_table() else {
+        bail!("[build_env] in {path} must contain per-platform tables");
+    };
+    let mut env = BTreeMap::new();
+    for (section, vars) in sections {
+        let Some(vars) = vars.as_table() else {
+            bail!("build_env.{section} in {path} must be a table of `NAME = \"value\"`");
+        };
+        let mut parsed = BTreeMap::new();
+        for (name, value) in vars {
+            let Some(value) = value.as_str() else {
+                bail!("build_env.{section}.{name} in {path} must be a string");
+            };
+            parsed.insert(name.clone(), value.to_string());
+        }
+        env.insert(section.clone(), parsed);
+    }
+    Ok(env)
+}
+
+/// Parse the `[swift_settings.<TargetName>]` tables: per-target
+/// `language_version` and `defines` for the generated manifest.
+fn swift_settings(
+    table: &toml::Table,
+    path: &Utf8Path,
+) -> Result<BTreeMap<String, SwiftSettings>> {
+    let Some(value) = table.get("swift_settings") else {
+        return Ok(BTreeMap::new());
+    };
+    let Some(sections) = value.as_table() else {
+        bail!("[swift_settings] in {path} must contain per-target tables");
+    };
+    let mut settings = BTreeMap::new();
+    for (target, entries) in sections {
+        let Some(entries) = entries.as_table() else {
+            bail!("swift_settings.{target} in {path} must be a table");
+        };
+        let language_version = validated_choice(
+            entries,
+            path,
+            "language_version",
+            SWIFT_LANGUAGE_VERSIONS,
+        )?;
+        let mut defines = Vec::new();
+        if let Some(value) = entries.get("defines") {
+            let Some(values) = value.as_array() else {
+                bail!("swift_settings.{target}.defines in {path} must be an array of strings");
+            };
+            for define in values {
+                let Some(define) = define.as_str() else {
+                    bail!("swift_settings.{target}.defines in {path} must contain strings");
+                };
+                defines.push(define.to_string());
+            }
+        }
+        settings.insert(
+            target.clone(),
+            SwiftSettings {
+                language_version,
+                defines,
+            },
+        );
+    }
+    Ok(settings)
+}
+
+/// Parse the `[swift_target_dependencies]` table: `TargetName = ["Dep", …]`
+/// entries overriding the default wiring between the package's Swift source
+/// targets.
+fn swift_target_dependencies(
+    table: &toml::Table,
+    path: &Utf8Path,
+) -> Result<BTreeMap<String, Vec<String>>> {
+    let Some(value) = table.get("swift_target_dependencies") else {
+        return Ok(BTreeMap::new());
+    };
+    let Some(entries) = value.as_table() else {
+        bail!("[swift_target_dependencies] in {path} must be a table of `TargetName = [\"Dep\"]`");
+    };
+    let mut dependencies = BTreeMap::new();
+    for (target, deps) in entries {
+        let Some(deps) = deps.as_array() else {
+            bail!("swift_target_dependencies.{target} in {path} must be an array of target names");
+        };
+        let mut names = Vec::new();
+        for dep in deps {
+            let Some(dep) = dep.as_str() else {
+                bail!("swift_target_dependencies.{target} in {path} must contain strings");
+            };
+            names.push(dep.to_string());
+        }
+        dependencies.insert(target.clone(), names);
+    }
+    Ok(dependencies)
+}
+
+/// PascalCase a crate name: `wp-api_core` becomes `WpApiCore`. Used to
+/// derive Swift module names when `uniffi.toml` doesn't set them.
+fn pascal_case(name: &str) -> String {
+    name.split(['-', '_'])
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// Parse the `[external_types]` table: `TypeName = "SwiftModule"` entries,
+/// sorted by type name for stable output.
+fn external_types(table: &toml::Table, path: &Utf8Path) -> Result<Vec<ExternalType>> {
+    let Some(value) = table.get("external_types") else {
+        return Ok(Vec::new());
+    };
+    let Some(entries) = value.as_table() else {
+        bail!("[external_types] in {path} must be a table of `TypeName = \"SwiftModule\"`");
+    };
+    let mut types = Vec::new();
+    for (name, module) in entries {
+        let Some(module) = module.as_str() else {
+            bail!("external_types.{name} in {path} must be a string module name");
+        };
+        types.push(ExternalType {
+            name: name.clone(),
+            module: module.to_string(),
+        });
+    }
+    types.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(types)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pascal_case_crate_names() {
+        assert_eq!(pascal_case("wp-api"), "WpApi");
+        assert_eq!(pascal_case("my_ffi_crate"), "MyFfiCrate");
+        assert_eq!(pascal_case("single"), "Single");
+    }
+}
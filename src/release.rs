@@ -0,0 +1,218 @@
+//! Release automation.
+//!
+//! Cutting a release used to be a manual runbook: bump the crate versions,
+//! zip the XCFramework, compute its SPM checksum, point the manifest's binary
+//! target at the release URL, tag. Each step is easy; forgetting one ships a
+//! manifest whose checksum doesn't match the uploaded archive. `release`
+//! runs them in order from a single command.
+
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+
+use crate::project::Project;
+use crate::utils::ExecuteCommand;
+
+/// Cut a release: set `version` in every in-workspace UniFFI crate, archive
+/// the built XCFramework with its SPM checksum and a provenance statement,
+/// rewrite the manifest's binary target to the release URL, and optionally
+/// create an annotated git tag.
+///
+/// Requires `release_url_template` in `uniffi.toml` (with `{version}` and
+/// `{name}` placeholders) and a previously built XCFramework. Uploading the
+/// archive to the release URL stays a manual step.
+pub fn release(version: &str, tag: bool) -> crate::Result<()> {
+    let run = || -> Result<()> {
+        let project = Project::from_current_dir()?;
+        let Some(template) = &project.release_url_template else {
+            bail!(
+                "No release_url_template in uniffi.toml. Add e.g. \
+                 release_url_template = \"https://example.com/releases/{{version}}/{{name}}.xcframework.zip\""
+            );
+        };
+        let xcframework = project.xcframework_path();
+        if !xcframework.exists() {
+            bail!("No XCFramework at {xcframework}. Run `uniffi-swift-helper build` first.");
+        }
+
+        for package in &project.uniffi_packages {
+            if package.is_in_workspace(project.workspace_root()) {
+                bump_crate_version(&package.package.manifest_path, version)?;
+            }
+        }
+
+        let archive = archive_xcframework(&project, version)?;
+        let checksum = compute_checksum(&archive)?;
+        let url = template
+            .replace("{version}", version)
+            .replace("{name}", &project.ffi_module_name);
+        rewrite_binary_targets(&project, &url, &checksum)?;
+        let provenance = write_provenance(&archive, &checksum, version)?;
+
+        println!("Archived {archive}");
+        println!("  url:        {url}");
+        println!("  checksum:   {checksum}");
+        println!("  provenance: {provenance}");
+        println!("Upload the archive to that URL before publishing the manifest.");
+
+        if tag {
+            Command::new("git")
+                .args(["tag", "-a", &format!("v{version}"), "-m"])
+                .arg(format!("Release {version}"))
+                .successful_output()?;
+            println!("Tagged v{version}");
+        }
+        Ok(())
+    };
+    run().map_err(crate::Error::from)
+}
+
+/// Set the `version` key in a crate manifest's `[package]` section.
+fn bump_crate_version(manifest_path: &Utf8Path, version: &str) -> Result<()> {
+    let contents = std::fs::read_to_string(manifest_path)
+        .with_context(|| format!("Can't read {manifest_path}"))?;
+    let mut lines: Vec<String> = Vec::new();
+    let mut in_package = false;
+    let mut bumped = false;
+    for line in contents.lines() {
+        if line.trim_start().starts_with('[') {
+            in_package = line.trim() == "[package]";
+        }
+        if in_package && !bumped && line.trim_start().starts_with("version") {
+            lines.push(format!("version = \"{version}\""));
+            bumped = true;
+            continue;
+        }
+        lines.push(line.to_string());
+    }
+    if !bumped {
+        bail!("No version key in the [package] section of {manifest_path}");
+    }
+    std::fs::write(manifest_path, lines.join("\n") + "\n")
+        .with_context(|| format!("Can't write {manifest_path}"))?;
+    println!("Set version {version} in {manifest_path}");
+    Ok(())
+}
+
+/// Zip the XCFramework into `<output_root>/release/` with ditto, the same
+/// tool Xcode's organizer uses, so resource forks and symlinks survive.
+fn archive_xcframework(project: &Project, version: &str) -> Result<Utf8PathBuf> {
+    let release_dir = project.output_root().join("release");
+    std::fs::create_dir_all(&release_dir)
+        .with_context(|| format!("Can't create {release_dir}"))?;
+    let archive = release_dir.join(format!(
+        "{}-{version}.xcframework.zip",
+        project.ffi_module_name
+    ));
+    Command::new("ditto")
+        .args(["-c", "-k", "--keepParent"])
+        .arg(project.xcframework_path())
+        .arg(&archive)
+        .successful_output()?;
+    Ok(archive)
+}
+
+/// Write an in-toto/SLSA provenance statement next to the archive, recording
+/// the builder, the source revision, and the archive's digest, so consumers
+/// can verify where the binary came from. The SPM checksum doubles as the
+/// subject digest: `swift package compute-checksum` is SHA-256 of the file.
+///
+/// The statement is unsigned; CI pipelines that want a verifiable
+/// attestation sign it with their own key (e.g. `cosign attest`).
+fn write_provenance(archive: &Utf8Path, checksum: &str, version: &str) -> Result<Utf8PathBuf> {
+    let source = git_output(&["remote", "get-url", "origin"]);
+    let revision = git_output(&["rev-parse", "HEAD"]);
+    let dirty = git_output(&["status", "--porcelain"]).is_some_and(|s| !s.is_empty());
+    let finished_on = Command::new("date")
+        .args(["-u", "+%Y-%m-%dT%H:%M:%SZ"])
+        .successful_output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())?;
+
+    let statement = serde_json::json!({
+        "_type": "https://in-toto.io/Statement/v1",
+        "subject": [{
+            "name": archive.file_name().unwrap_or(archive.as_str()),
+            "digest": { "sha256": checksum },
+        }],
+        "predicateType": "https://slsa.dev/provenance/v1",
+        "predicate": {
+            "buildDefinition": {
+                "buildType": "https://github.com/Automattic/uniffi-swift-helper",
+                "externalParameters": {
+                    "command": format!("uniffi-swift-helper release {version}"),
+                    "version": version,
+                },
+                "resolvedDependencies": [{
+                    "uri": source,
+                    "digest": { "gitCommit": revision },
+                    "dirty": dirty,
+                }],
+            },
+            "runDetails": {
+                "builder": {
+                    "id": format!("uniffi-swift-helper/{}", env!("CARGO_PKG_VERSION")),
+                },
+                "metadata": { "finishedOn": finished_on },
+            },
+        },
+    });
+
+    let path = Utf8PathBuf::from(format!("{archive}.provenance.json"));
+    let contents = serde_json::to_string_pretty(&statement)?;
+    std::fs::write(&path, contents + "\n").with_context(|| format!("Can't write {path}"))?;
+    Ok(path)
+}
+
+/// The trimmed stdout of a git command, or `None` when git fails (e.g. no
+/// `origin` remote). Provenance fields degrade to `null` rather than failing
+/// the release.
+fn git_output(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).successful_output().ok()?;
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// The checksum SPM will verify the downloaded archive against.
+fn compute_checksum(archive: &Utf8Path) -> Result<String> {
+    let output = Command::new("swift")
+        .args(["package", "compute-checksum", archive.as_str()])
+        .successful_output()?;
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Point every `.binaryTarget(name:path:)` in the workspace `Package.swift`
+/// at the release URL and checksum instead of the local build output.
+fn rewrite_binary_targets(project: &Project, url: &str, checksum: &str) -> Result<()> {
+    let manifest_path = project.workspace_root().join("Package.swift");
+    let contents = std::fs::read_to_string(&manifest_path)
+        .with_context(|| format!("Can't read {manifest_path}"))?;
+    let mut lines: Vec<String> = Vec::new();
+    let mut rewritten = false;
+    for line in contents.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with(".binaryTarget(name: ") && trimmed.contains("path: ") {
+            let indent = &line[..line.len() - trimmed.len()];
+            let name_start = ".binaryTarget(name: \"".len();
+            let name: String = trimmed[name_start..]
+                .chars()
+                .take_while(|c| *c != '"')
+                .collect();
+            lines.push(format!(
+                "{indent}.binaryTarget(name: \"{name}\", url: \"{url}\", checksum: \"{checksum}\"),"
+            ));
+            rewritten = true;
+            continue;
+        }
+        lines.push(line.to_string());
+    }
+    if !rewritten {
+        bail!(
+            "No local .binaryTarget entries in {manifest_path}; \
+             run `uniffi-swift-helper generate-package` first"
+        );
+    }
+    std::fs::write(&manifest_path, lines.join("\n") + "\n")
+        .with_context(|| format!("Can't write {manifest_path}"))?;
+    println!("Rewrote the binary targets in {manifest_path}");
+    Ok(())
+}
@@ -0,0 +1,106 @@
+//! Reproducibility verification.
+//!
+//! Supply-chain audits want evidence that the shipped XCFramework can be
+//! reproduced from source. `verify-reproducible` builds it twice and compares
+//! the artifacts file by file, so any nondeterminism (embedded timestamps,
+//! unstable ordering, host paths) is caught with a concrete file name instead
+//! of a mismatching release hash months later.
+
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Context, Result};
+use camino::Utf8Path;
+
+use crate::build::{build, BuildOptions};
+use crate::events::Reporter;
+use crate::project::Project;
+use crate::xcframework::ApplePlatform;
+
+/// Build the XCFramework twice and fail when the artifacts differ.
+///
+/// With `clean`, the second build runs in a scratch `CARGO_TARGET_DIR`, also
+/// catching nondeterminism that incremental reuse of the first build's
+/// artifacts would mask.
+pub fn verify_reproducible(
+    platforms: &[ApplePlatform],
+    profile: &str,
+    options: &BuildOptions,
+    clean: bool,
+    reporter: &Reporter,
+) -> crate::Result<()> {
+    let run = || -> Result<()> {
+        let project = Project::from_current_dir()?;
+        let xcframework = project.xcframework_path();
+
+        build(platforms, profile, options, reporter)?;
+        let first = artifact_hashes(&xcframework)?;
+
+        if clean {
+            // Subsequent cargo invocations (and cargo metadata) pick the
+            // scratch directory up from the environment.
+            std::env::set_var("CARGO_TARGET_DIR", project.tmp_dir("reproducible"));
+        }
+        build(platforms, profile, options, reporter)?;
+        let second_xcframework = if clean {
+            Project::from_current_dir()?.xcframework_path()
+        } else {
+            xcframework.clone()
+        };
+        let second = artifact_hashes(&second_xcframework)?;
+
+        let mut differing: Vec<&String> = first
+            .iter()
+            .filter(|(path, hash)| second.get(*path) != Some(hash))
+            .map(|(path, _)| path)
+            .collect();
+        differing.extend(second.keys().filter(|path| !first.contains_key(*path)));
+        if differing.is_empty() {
+            println!(
+                "Build is reproducible: {} files match across both builds.",
+                first.len()
+            );
+            return Ok(());
+        }
+        for path in &differing {
+            eprintln!("Differs between builds: {path}");
+        }
+        bail!(
+            "{} file(s) differ between two builds of {xcframework}",
+            differing.len()
+        );
+    };
+    run().map_err(crate::Error::from)
+}
+
+/// Content hash of every file in the XCFramework, keyed by relative path.
+fn artifact_hashes(xcframework: &Utf8Path) -> Result<BTreeMap<String, u64>> {
+    let mut hashes = BTreeMap::new();
+    collect_hashes(xcframework, xcframework, &mut hashes)?;
+    Ok(hashes)
+}
+
+fn collect_hashes(
+    root: &Utf8Path,
+    dir: &Utf8Path,
+    hashes: &mut BTreeMap<String, u64>,
+) -> Result<()> {
+    for entry in dir
+        .read_dir_utf8()
+        .with_context(|| format!("Can't read {dir}"))?
+    {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            collect_hashes(root, entry.path(), hashes)?;
+        } else {
+            let contents = std::fs::read(entry.path())
+                .with_context(|| format!("Can't read {}", entry.path()))?;
+            let relative = entry
+                .path()
+                .strip_prefix(root)
+                .expect("entries are always under the root")
+                .to_string();
+            hashes.insert(relative, crate::utils::fnv1a_64(&contents));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,1311 @@
+use std::fmt;
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use rinja::Template;
+
+use crate::deployment::DeploymentTargets;
+use crate::events::{BuildPhase, Reporter};
+use crate::project::{ExternalType, Project, UniffiPackage};
+use crate::utils::{fs, ExecuteCommand};
+use crate::xcframework::FrameworkLayout;
+
+/// Optional behaviors of [`generate_swift_package`].
+#[derive(Default)]
+pub struct GeneratePackageOptions {
+    /// Only generate targets for these UniFFI packages (all when empty).
+    pub packages: Vec<String>,
+
+    /// The layout the XCFrameworks were built with.
+    pub layout: FrameworkLayout,
+
+    /// Also generate an umbrella `<FfiModuleName>Kit` product re-exporting
+    /// every public module, so host apps can depend on a single name.
+    pub umbrella: bool,
+
+    /// Read minimum OS versions from this `.xcodeproj` or xcconfig file and
+    /// declare them as the package's platforms.
+    pub deployment_targets_from: Option<Utf8PathBuf>,
+
+    /// Emit placeholder bindings targets when the wrappers haven't been
+    /// generated yet, instead of failing. Lets a fresh checkout regenerate
+    /// `Package.swift` before the first build.
+    pub allow_missing_wrappers: bool,
+
+    /// Symlink the Swift sources of path dependencies into `target/` instead
+    /// of copying them, so edits in the dependency repo are picked up by SPM
+    /// immediately. Git dependencies are still copied.
+    pub symlink_vendored: bool,
+
+    /// swift-format configuration applied when formatting the manifest (and
+    /// the wrappers, with [`Self::format_wrappers`]), for teams whose
+    /// `.swift-format` lives in a non-standard location.
+    pub format_config: Option<Utf8PathBuf>,
+
+    /// Also run swift-format over the generated wrapper sources, with the
+    /// checksums re-recorded so the reformat doesn't count as a manual edit.
+    pub format_wrappers: bool,
+
+    /// Also generate a `<FfiModuleName>SmokeTests` test target that runs
+    /// uniffi's contract-version and API-checksum verification per module,
+    /// so "bindings and library out of sync" fails `swift test` instead of
+    /// the host app at runtime.
+    pub smoke_test: bool,
+}
+
+/// Generate `Package.swift` at the workspace root, wiring the XCFramework,
+/// the generated bindings targets, and the hand-written wrapper sources.
+pub fn generate_swift_package(options: &GeneratePackageOptions) -> crate::Result<()> {
+    let run = || -> Result<()> {
+        let (project, manifest) = rendered_manifest(options)?;
+        let manifest_path = project.workspace_root().join("Package.swift");
+        let _lock = crate::utils::WorkspaceLock::acquire(project.target_dir())?;
+        std::fs::write(&manifest_path, manifest)
+            .with_context(|| format!("Can't write {manifest_path}"))?;
+        println!("Generated {manifest_path}");
+        Ok(())
+    };
+    run().map_err(crate::Error::from)
+}
+
+/// Check that the committed `Package.swift` matches what
+/// [`generate_swift_package`] would produce. Prints a diff and fails when
+/// they differ, so CI catches layout or uniffi.toml changes made without
+/// rerunning generate-package.
+pub fn verify_swift_package(options: &GeneratePackageOptions) -> crate::Result<()> {
+    let run = || -> Result<()> {
+        let (project, expected) = rendered_manifest(options)?;
+        let manifest_path = project.workspace_root().join("Package.swift");
+        let actual = std::fs::read_to_string(&manifest_path)
+            .with_context(|| format!("Can't read {manifest_path}"))?;
+        if actual == expected {
+            println!("{manifest_path} is up to date");
+            return Ok(());
+        }
+        print_diff(&actual, &expected);
+        bail!("{manifest_path} is out of date. Run `uniffi-swift-helper generate-package`.");
+    };
+    run().map_err(crate::Error::from)
+}
+
+/// Show how the checked-in manifest differs from the regenerated one:
+/// common prefix and suffix are elided down to a few context lines.
+fn print_diff(actual: &str, expected: &str) {
+    let actual: Vec<&str> = actual.lines().collect();
+    let expected: Vec<&str> = expected.lines().collect();
+    let prefix = actual
+        .iter()
+        .zip(&expected)
+        .take_while(|(a, e)| a == e)
+        .count();
+    let suffix = actual[prefix..]
+        .iter()
+        .rev()
+        .zip(expected[prefix..].iter().rev())
+        .take_while(|(a, e)| a == e)
+        .count();
+
+    for line in &actual[prefix.saturating_sub(3)..prefix] {
+        eprintln!("  {line}");
+    }
+    for line in &actual[prefix..actual.len() - suffix] {
+        eprintln!("- {line}");
+    }
+    for line in &expected[prefix..expected.len() - suffix] {
+        eprintln!("+ {line}");
+    }
+    for line in &actual[actual.len() - suffix..(actual.len() - suffix + 3).min(actual.len())] {
+        eprintln!("  {line}");
+    }
+}
+
+/// Render and format the manifest, returning the project it belongs to and
+/// the formatted contents.
+fn rendered_manifest(options: &GeneratePackageOptions) -> Result<(Project, String)> {
+    let layout = options.layout;
+    let mut project = Project::from_current_dir()?;
+    project.select_packages(&options.packages)?;
+    let project = project;
+
+    let mut products = Vec::new();
+    // When both linkage variants were packaged, the manifest interpolates
+    // the variant chosen through UNIFFI_LINKAGE into the binary target path.
+    let linkage_selection = project.variant_xcframework_path("static").exists()
+        && project.variant_xcframework_path("dynamic").exists();
+    let mut targets = match layout {
+        FrameworkLayout::Merged => vec![SwiftTarget {
+            name: project.ffi_module_name.clone(),
+            kind: SwiftTargetKind::Binary,
+            path: if linkage_selection {
+                relative_to_root(&project, &project.variant_xcframework_path("\\(linkage)"))
+            } else {
+                relative_to_root(&project, &project.xcframework_path())
+            },
+            dependencies: Vec::new(),
+            settings: Vec::new(),
+            linker_settings: Vec::new(),
+        }],
+        FrameworkLayout::PerCrate => project
+            .uniffi_packages
+            .iter()
+            .map(|package| SwiftTarget {
+                name: package.ffi_module_name(),
+                kind: SwiftTargetKind::Binary,
+                path: relative_to_root(&project, &project.crate_xcframework_path(package)),
+                dependencies: Vec::new(),
+                settings: Vec::new(),
+                linker_settings: Vec::new(),
+            })
+            .collect(),
+    };
+
+    for package in &project.uniffi_packages {
+        let swift_dir = if package.is_in_workspace(project.workspace_root()) {
+            package.swift_source_dir()
+        } else {
+            vend_swift_source_code(&project, package, options.symlink_vendored)?
+        };
+
+        targets.push(internal_target(
+            &project,
+            package,
+            layout,
+            options.allow_missing_wrappers,
+        )?);
+        let (mut source_targets, mut test_targets) = source_targets(&project, package, &swift_dir)?;
+        products.push(package.public_module_name.clone());
+        targets.append(&mut source_targets);
+        targets.append(&mut test_targets);
+    }
+
+    if options.umbrella {
+        let target = umbrella_target(&project)?;
+        products.push(target.name.clone());
+        targets.push(target);
+    }
+
+    if options.smoke_test {
+        targets.push(smoke_test_target(&project)?);
+    }
+
+    // A configured `[products]` table replaces the default one-product-per-
+    // module mapping, so one product can expose several wrapper targets.
+    let products: Vec<SwiftProduct> = if project.products.is_empty() {
+        products
+            .into_iter()
+            .map(|name| SwiftProduct {
+                targets: vec![name.clone()],
+                name,
+            })
+            .collect()
+    } else {
+        for (product, product_targets) in &project.products {
+            for target in product_targets {
+                if !targets.iter().any(|t| t.name == *target) {
+                    bail!(
+                        "Product {product} in uniffi.toml references unknown target {target}. \
+                         Known targets: {}",
+                        targets.iter().map(|t| t.name.as_str()).collect::<Vec<_>>().join(", ")
+                    );
+                }
+            }
+        }
+        project
+            .products
+            .iter()
+            .map(|(name, product_targets)| SwiftProduct {
+                name: name.clone(),
+                targets: product_targets.clone(),
+            })
+            .collect()
+    };
+
+    // Per-target compiler settings declared in uniffi.toml, matched by name
+    // so they can address bindings, wrapper, and test targets alike.
+    for target in &mut targets {
+        if let Some(settings) = project.swift_settings.get(&target.name) {
+            if let Some(version) = &settings.language_version {
+                target
+                    .settings
+                    .push(format!(".swiftLanguageVersion(.version(\"{version}\"))"));
+            }
+            for define in &settings.defines {
+                target.settings.push(format!(".define(\"{define}\")"));
+            }
+        }
+    }
+
+    // Minimum OS versions tracked from the host app's project, when given.
+    let platforms = match &options.deployment_targets_from {
+        Some(path) => DeploymentTargets::from_path(path)?.spm_platforms(),
+        None => Vec::new(),
+    };
+
+    // Surface which commit each git dependency's bindings were generated
+    // from, so a stale Package.swift is diagnosable from the manifest alone.
+    let pinned_revisions = project
+        .uniffi_packages
+        .iter()
+        .filter_map(|package| {
+            let revision = package.git_revision()?;
+            Some((package.package.name.to_string(), revision.to_string()))
+        })
+        .collect();
+
+    let manifest = PackageManifest {
+        name: project.ffi_module_name.clone(),
+        tools_version: project.swift_tools_version.clone(),
+        language_version: project.swift_language_version.clone(),
+        linkage_selection,
+        pinned_revisions,
+        platforms,
+        products,
+        targets,
+    }
+    .render()
+    .context("Can't render Package.swift")?;
+
+    let tmp_dir = project.tmp_dir("package-manifest");
+    std::fs::create_dir_all(&tmp_dir).with_context(|| format!("Can't create {tmp_dir}"))?;
+    let tmp_file = tmp_dir.join("Package.swift");
+    std::fs::write(&tmp_file, manifest).with_context(|| format!("Can't write {tmp_file}"))?;
+    let mut cmd = Command::new("swift");
+    cmd.args(["format", "--in-place"]);
+    if let Some(config) = &options.format_config {
+        cmd.args(["--configuration", config.as_str()]);
+    }
+    cmd.arg(&tmp_file);
+    cmd.successful_output()?;
+    let formatted =
+        std::fs::read_to_string(&tmp_file).with_context(|| format!("Can't read {tmp_file}"))?;
+
+    if options.format_wrappers {
+        format_wrappers(&project, options.format_config.as_deref())?;
+    }
+
+    Ok((project, formatted))
+}
+
+/// Run swift-format over the generated wrapper sources, then re-record the
+/// checksums so the reformat isn't flagged as a manual edit next build.
+fn format_wrappers(project: &Project, config: Option<&Utf8Path>) -> Result<()> {
+    let wrapper_dir = project.swift_wrapper_dir();
+    if !wrapper_dir.exists() {
+        return Ok(());
+    }
+    let mut cmd = Command::new("swift");
+    cmd.args(["format", "--in-place", "--recursive"]);
+    if let Some(config) = config {
+        cmd.args(["--configuration", config.as_str()]);
+    }
+    cmd.arg(&wrapper_dir);
+    cmd.successful_output()?;
+    write_wrapper_checksums(&wrapper_dir)
+}
+
+/// The SPM target for a package's generated bindings, pointing at the
+/// post-processed sources in the swift-wrapper directory.
+pub(crate) fn internal_target(
+    project: &Project,
+    package: &UniffiPackage,
+    layout: FrameworkLayout,
+    allow_missing: bool,
+) -> Result<SwiftTarget> {
+    let dir = project
+        .swift_wrapper_dir()
+        .join(&package.internal_module_name);
+    if !dir.exists() {
+        if !allow_missing {
+            bail!(
+                "No generated wrapper found at {dir}. Run `uniffi-swift-helper build` first, \
+                 or pass --allow-missing-wrappers to emit a placeholder."
+            );
+        }
+        // A placeholder source keeps the target resolvable until the first
+        // build replaces this directory with the real wrappers.
+        std::fs::create_dir_all(&dir).with_context(|| format!("Can't create {dir}"))?;
+        let placeholder = dir.join("Placeholder.swift");
+        std::fs::write(
+            &placeholder,
+            "// Placeholder generated by uniffi-swift-helper generate-package.\n\
+             // Run `uniffi-swift-helper build` to generate the real bindings.\n",
+        )
+        .with_context(|| format!("Can't write {placeholder}"))?;
+    }
+    let ffi_target = match layout {
+        FrameworkLayout::Merged => project.ffi_module_name.clone(),
+        FrameworkLayout::PerCrate => package.ffi_module_name(),
+    };
+    let mut dependencies = vec![ffi_target];
+    dependencies.extend(uniffi_dependency_modules(project, package));
+    Ok(SwiftTarget {
+        name: package.internal_module_name.clone(),
+        kind: SwiftTargetKind::Target,
+        path: relative_to_root(project, &dir),
+        dependencies,
+        settings: Vec::new(),
+        linker_settings: linker_settings(project),
+    })
+}
+
+/// The `linkerSettings` entries declaring the system libraries and frameworks
+/// the Rust code needs, from `link_libraries`/`link_frameworks` in
+/// `uniffi.toml`. They go on the bindings target, which is what links the
+/// FFI binary into the consumer.
+fn linker_settings(project: &Project) -> Vec<String> {
+    let libraries = project
+        .link_libraries
+        .iter()
+        .map(|library| format!(".linkedLibrary(\"{library}\")"));
+    let frameworks = project
+        .link_frameworks
+        .iter()
+        .map(|framework| format!(".linkedFramework(\"{framework}\")"));
+    libraries.chain(frameworks).collect()
+}
+
+/// The SPM targets for a package's hand-written wrapper sources, one per
+/// subdirectory of `native/swift/Sources`, plus a test target per
+/// subdirectory of `native/swift/Tests`. Packages without a `Tests`
+/// directory simply get no test targets.
+///
+/// One source directory must match the package's public module name; that
+/// target is exposed as the product. By default every source target depends
+/// on the generated bindings module, and the public module additionally
+/// depends on its sibling modules; `[swift_target_dependencies]` in
+/// `uniffi.toml` overrides the wiring per target.
+pub(crate) fn source_targets(
+    project: &Project,
+    package: &UniffiPackage,
+    swift_dir: &Utf8Path,
+) -> Result<(Vec<SwiftTarget>, Vec<SwiftTarget>)> {
+    let sources_dirs = fs::subdirs(&swift_dir.join("Sources"))?;
+    let module_names: Vec<String> = match sources_dirs.as_slice() {
+        [] => bail!("Expected a subdirectory in {swift_dir}/Sources, found none"),
+        // A single module keeps its historical name from uniffi.toml, even
+        // when the directory is named differently.
+        [_] => vec![package.public_module_name.clone()],
+        _ => {
+            let names: Vec<String> = sources_dirs
+                .iter()
+                .map(|dir| {
+                    dir.file_name()
+                        .expect("subdirectories always have a name")
+                        .to_string()
+                })
+                .collect();
+            if !names.contains(&package.public_module_name) {
+                bail!(
+                    "{swift_dir}/Sources has no directory named {}, the public module of {}",
+                    package.public_module_name,
+                    package.package.name
+                );
+            }
+            names
+        }
+    };
+
+    let mut targets = Vec::new();
+    for (dir, name) in sources_dirs.iter().zip(&module_names) {
+        let mut dependencies = match package.swift_target_dependencies.get(name) {
+            Some(overrides) => overrides.clone(),
+            None if *name == package.public_module_name => module_names
+                .iter()
+                .filter(|sibling| *sibling != name)
+                .cloned()
+                .collect(),
+            None => Vec::new(),
+        };
+        dependencies.insert(0, package.internal_module_name.clone());
+        targets.push(SwiftTarget {
+            name: name.clone(),
+            kind: SwiftTargetKind::Target,
+            path: relative_to_root(project, dir),
+            dependencies,
+            settings: Vec::new(),
+            linker_settings: Vec::new(),
+        });
+    }
+
+    let tests_dir = swift_dir.join("Tests");
+    let mut test_targets = Vec::new();
+    if tests_dir.exists() {
+        for dir in fs::subdirs(&tests_dir)? {
+            test_targets.push(SwiftTarget {
+                name: dir
+                    .file_name()
+                    .expect("subdirectories always have a name")
+                    .to_string(),
+                kind: SwiftTargetKind::TestTarget,
+                path: relative_to_root(project, &dir),
+                dependencies: vec![package.public_module_name.clone()],
+                settings: Vec::new(),
+                linker_settings: Vec::new(),
+            });
+        }
+    }
+
+    Ok((targets, test_targets))
+}
+
+/// Write the umbrella module's single source file, `@_exported import`ing
+/// every public module, and describe its SPM target.
+fn umbrella_target(project: &Project) -> Result<SwiftTarget> {
+    let name = format!("{}Kit", project.ffi_module_name);
+    let dir = project.umbrella_dir().join(&name);
+    fs::recreate_dir(&dir)?;
+
+    let mut contents =
+        String::from("// This file was generated by uniffi-swift-helper. Do not edit by hand.\n\n");
+    for package in &project.uniffi_packages {
+        contents.push_str("@_exported import ");
+        contents.push_str(&package.public_module_name);
+        contents.push('\n');
+    }
+    let source = dir.join("Exports.swift");
+    std::fs::write(&source, contents).with_context(|| format!("Can't write {source}"))?;
+
+    Ok(SwiftTarget {
+        name,
+        kind: SwiftTargetKind::Target,
+        path: relative_to_root(project, &dir),
+        dependencies: project
+            .uniffi_packages
+            .iter()
+            .map(|p| p.public_module_name.clone())
+            .collect(),
+        settings: Vec::new(),
+        linker_settings: Vec::new(),
+    })
+}
+
+/// Write the smoke-test target's single source file, one test per UniFFI
+/// package running the contract verification shim (see
+/// [`verify_contract_shim`]) plus a reference to the first generated record
+/// so the module's symbols actually get linked, and describe its SPM target.
+fn smoke_test_target(project: &Project) -> Result<SwiftTarget> {
+    let name = format!("{}SmokeTests", project.ffi_module_name);
+    let dir = project.smoke_tests_dir().join(&name);
+    fs::recreate_dir(&dir)?;
+
+    let mut contents =
+        String::from("// This file was generated by uniffi-swift-helper. Do not edit by hand.\n\n");
+    contents.push_str("import XCTest\n");
+    for package in &project.uniffi_packages {
+        contents.push_str("import ");
+        contents.push_str(&package.internal_module_name);
+        contents.push('\n');
+    }
+    contents.push_str(&format!("\nfinal class {name}: XCTestCase {{\n"));
+    for package in &project.uniffi_packages {
+        let module = &package.internal_module_name;
+        contents.push_str(&format!(
+            "    func test{module}Contract() {{\n        {module}.uniffiVerifyContract()\n"
+        ));
+        if let Some(record) = first_generated_record(project, package) {
+            contents.push_str(&format!("        XCTAssertNotNil({record}.self)\n"));
+        }
+        contents.push_str("    }\n");
+    }
+    contents.push_str("}\n");
+    let source = dir.join("SmokeTests.swift");
+    std::fs::write(&source, contents).with_context(|| format!("Can't write {source}"))?;
+
+    Ok(SwiftTarget {
+        name,
+        kind: SwiftTargetKind::TestTarget,
+        path: relative_to_root(project, &dir),
+        dependencies: project
+            .uniffi_packages
+            .iter()
+            .map(|p| p.internal_module_name.clone())
+            .collect(),
+        settings: Vec::new(),
+        linker_settings: Vec::new(),
+    })
+}
+
+/// Scaffold a minimal XCTest target for every in-workspace UniFFI package
+/// that has no `Tests` directory yet, then regenerate `Package.swift` so the
+/// new targets are wired in. The scaffold references a generated record when
+/// one can be found in the wrapper sources, as a starting point for a real
+/// round-trip test. Out-of-workspace packages are skipped: their tests live
+/// in their own repository.
+pub fn generate_test_scaffolds(options: &GeneratePackageOptions) -> crate::Result<()> {
+    let run = || -> Result<()> {
+        let project = Project::from_current_dir()?;
+        let mut scaffolded = false;
+        for package in &project.uniffi_packages {
+            if !package.is_in_workspace(project.workspace_root()) {
+                continue;
+            }
+            let tests_dir = package.swift_source_dir().join("Tests");
+            if tests_dir.exists() {
+                println!("{}: already has a Tests directory", package.package.name);
+                continue;
+            }
+            let target_dir = tests_dir.join(format!("{}Tests", package.public_module_name));
+            std::fs::create_dir_all(&target_dir)
+                .with_context(|| format!("Can't create {target_dir}"))?;
+            let source = target_dir.join(format!("{}Tests.swift", package.public_module_name));
+            std::fs::write(&source, test_scaffold(&project, package))
+                .with_context(|| format!("Can't write {source}"))?;
+            println!("Scaffolded {source}");
+            scaffolded = true;
+        }
+        if !scaffolded {
+            println!("Every package already has tests; nothing to scaffold");
+            return Ok(());
+        }
+        Ok(())
+    };
+    run().map_err(crate::Error::from)?;
+    // source_targets picks the new directories up automatically; regenerating
+    // here saves the separate generate-package run.
+    generate_swift_package(options)
+}
+
+/// The contents of a scaffolded test file: a test class importing the public
+/// module, seeded with the first generated record found in the wrapper
+/// sources so the round-trip test has a concrete type to start from.
+fn test_scaffold(project: &Project, package: &UniffiPackage) -> String {
+    let module = &package.public_module_name;
+    let body = match first_generated_record(project, package) {
+        Some(record) => format!(
+            "    func test{record}RoundTrips() throws {{\n\
+             \x20       XCTAssertNotNil({record}.self)\n\
+             \x20       // TODO: construct a {record} and assert it survives a\n\
+             \x20       // round trip through the generated bindings.\n\
+             \x20   }}\n"
+        ),
+        None => "    func testModuleLinks() throws {\n\
+             \x20       // TODO: exercise the generated API. This placeholder only\n\
+             \x20       // checks that the module links.\n\
+             \x20   }\n"
+            .to_string(),
+    };
+    format!(
+        "// Scaffolded by uniffi-swift-helper generate-tests. Extend with real tests.\n\n\
+         import XCTest\n\
+         @testable import {module}\n\n\
+         final class {module}Tests: XCTestCase {{\n\
+         {body}\
+         }}\n"
+    )
+}
+
+/// The first record (`public struct`) declared in the package's generated
+/// wrapper sources, if any have been generated yet.
+fn first_generated_record(project: &Project, package: &UniffiPackage) -> Option<String> {
+    let module_dir = project
+        .swift_wrapper_dir()
+        .join(&package.internal_module_name);
+    for source in fs::files_with_extension(&module_dir, "swift").ok()? {
+        let contents = std::fs::read_to_string(source).ok()?;
+        for line in contents.lines() {
+            if let Some(rest) = line.strip_prefix("public struct ") {
+                let name: String = rest
+                    .chars()
+                    .take_while(|c| c.is_alphanumeric() || *c == '_')
+                    .collect();
+                if !name.is_empty() {
+                    return Some(name);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Refresh the vendored copies of all out-of-workspace packages' Swift
+/// sources, or with `check` only report which copies are stale. Gives CI and
+/// release scripts an explicit step instead of relying on the warning
+/// printed during generate-package.
+pub fn vendor_swift_sources(check: bool) -> crate::Result<()> {
+    let run = || -> Result<()> {
+        let project = Project::from_current_dir()?;
+        let excludes = vendor_excludes(&project);
+        let mut stale = Vec::new();
+        for package in &project.uniffi_packages {
+            if package.is_in_workspace(project.workspace_root()) {
+                continue;
+            }
+            let name = package.package.name.as_str();
+            let source = package.swift_source_dir();
+            let destination = project.vendored_sources_dir().join(name);
+            if destination.symlink_metadata().is_ok_and(|m| m.is_symlink()) {
+                println!("{name}: Swift sources are symlinked; nothing to vendor");
+                continue;
+            }
+            if destination.exists()
+                && vendor_marker_matches(&destination, package)
+                && fs::tree_digest(&source, &excludes)? == fs::tree_digest(&destination, &excludes)?
+            {
+                println!("{name}: vendored Swift sources are up to date");
+                continue;
+            }
+            if check {
+                stale.push(name);
+            } else {
+                fs::recreate_dir(&destination)?;
+                fs::copy_dir_excluding(&source, &destination, &excludes)?;
+                write_vendor_marker(&destination, package)?;
+                println!("{name}: vendored Swift sources into {destination}");
+            }
+        }
+        if !stale.is_empty() {
+            bail!(
+                "Vendored Swift sources are out of date for: {}. \
+                 Run `uniffi-swift-helper vendor`.",
+                stale.join(", ")
+            );
+        }
+        Ok(())
+    };
+    run().map_err(crate::Error::from)
+}
+
+/// Marker file recording which resolved crate a vendored copy came from, so
+/// dependency updates are detected instead of serving stale sources.
+const VENDOR_MARKER_FILE: &str = ".vendored-from";
+
+/// Entries never worth vendoring: VCS metadata and SwiftPM build products.
+const DEFAULT_VENDOR_EXCLUDES: [&str; 3] = [".git", ".build", ".swiftpm"];
+
+/// The built-in exclude list plus any `vendor_excludes` from `uniffi.toml`.
+fn vendor_excludes(project: &Project) -> Vec<&str> {
+    DEFAULT_VENDOR_EXCLUDES
+        .into_iter()
+        .chain(project.vendor_excludes.iter().map(String::as_str))
+        .collect()
+}
+
+/// Whether the vendored copy at `destination` came from the currently
+/// resolved version of `package`.
+fn vendor_marker_matches(destination: &Utf8Path, package: &UniffiPackage) -> bool {
+    std::fs::read_to_string(destination.join(VENDOR_MARKER_FILE))
+        .is_ok_and(|marker| marker.trim() == package.package.id.repr)
+}
+
+/// Remove `path`, whether it is a directory or a symlink to one.
+fn remove_dir_or_symlink(path: &Utf8Path) -> Result<()> {
+    let metadata = path
+        .symlink_metadata()
+        .with_context(|| format!("Can't stat {path}"))?;
+    if metadata.is_symlink() {
+        std::fs::remove_file(path).with_context(|| format!("Can't remove {path}"))?;
+    } else {
+        std::fs::remove_dir_all(path).with_context(|| format!("Can't remove {path}"))?;
+    }
+    Ok(())
+}
+
+fn write_vendor_marker(destination: &Utf8Path, package: &UniffiPackage) -> Result<()> {
+    let path = destination.join(VENDOR_MARKER_FILE);
+    std::fs::write(&path, format!("{}\n", package.package.id.repr))
+        .with_context(|| format!("Can't write {path}"))?;
+    Ok(())
+}
+
+/// Copy an out-of-workspace package's Swift sources into `target/` so the
+/// generated manifest can reference them by relative path.
+///
+/// The resolved crate identity is recorded next to the copy: when the cargo
+/// dependency moves (version bump, new git revision), the sources are
+/// re-vendored automatically; an up-to-date copy is reused as-is.
+///
+/// With `symlink`, path dependencies are symlinked instead of copied.
+pub(crate) fn vend_swift_source_code(
+    project: &Project,
+    package: &UniffiPackage,
+    symlink: bool,
+) -> Result<Utf8PathBuf> {
+    let source = package.swift_source_dir();
+    if !source.exists() && crate::utils::offline() {
+        bail!(
+            "The Swift sources of {} are not available at {source} and can't \
+             be fetched in offline mode. Run `cargo fetch` online first.",
+            package.package.name
+        );
+    }
+    let destination = project
+        .vendored_sources_dir()
+        .join(package.package.name.as_str());
+    // Only path dependencies can be symlinked: a registry or git checkout
+    // lives in cargo's cache, which may be pruned or shared.
+    if symlink && package.package.source.is_none() {
+        if destination.symlink_metadata().is_ok() {
+            remove_dir_or_symlink(&destination)?;
+        }
+        if let Some(parent) = destination.parent() {
+            std::fs::create_dir_all(parent).with_context(|| format!("Can't create {parent}"))?;
+        }
+        std::os::unix::fs::symlink(&source, &destination)
+            .with_context(|| format!("Can't symlink {source} to {destination}"))?;
+        println!(
+            "Symlinked Swift sources of {} to {destination}.",
+            package.package.name
+        );
+        return Ok(destination);
+    }
+    if destination.symlink_metadata().is_ok_and(|m| m.is_symlink()) {
+        // A leftover symlink from a previous --symlink-vendored run; replace
+        // it with a real copy.
+        remove_dir_or_symlink(&destination)?;
+    }
+    if destination.exists() {
+        if vendor_marker_matches(&destination, package) {
+            return Ok(destination);
+        }
+        println!(
+            "Re-vendoring Swift sources of {}: the cargo dependency moved to {}.",
+            package.package.name, package.package.id.repr
+        );
+    }
+    fs::recreate_dir(&destination)?;
+    fs::copy_dir_excluding(&source, &destination, &vendor_excludes(project))?;
+    write_vendor_marker(&destination, package)?;
+    println!(
+        "Vendored Swift sources of {} into {destination}.",
+        package.package.name
+    );
+    Ok(destination)
+}
+
+/// Rewrite the uniffi-generated Swift sources for `target` into the
+/// swift-wrapper directory, one subdirectory per internal module.
+pub(crate) fn update_swift_wrappers(
+    project: &Project,
+    target: &str,
+    layout: FrameworkLayout,
+    strict: bool,
+    reporter: &Reporter,
+) -> Result<()> {
+    let bindings_dir = project.target_dir().join(target).join("swift-bindings");
+    let wrapper_dir = project.swift_wrapper_dir();
+    check_manual_edits(&wrapper_dir, strict)?;
+    fs::recreate_dir(&wrapper_dir)?;
+
+    // Each generated source is paired with its package and the FFI modules
+    // it needs to import; see [`ffi_modules`].
+    let mut sources: Vec<(Utf8PathBuf, &UniffiPackage)> = Vec::new();
+    match layout {
+        FrameworkLayout::Merged => {
+            for source in fs::files_with_extension(&bindings_dir, "swift")? {
+                let stem = source.file_stem().unwrap_or_default();
+                let Some(package) = project
+                    .uniffi_packages
+                    .iter()
+                    .find(|p| p.internal_module_name == stem)
+                else {
+                    eprintln!("Skipping {source}: no UniFFI package has module name {stem}");
+                    continue;
+                };
+                sources.push((source, package));
+            }
+        }
+        FrameworkLayout::PerCrate => {
+            for package in &project.uniffi_packages {
+                let crate_dir = bindings_dir.join(&package.internal_module_name);
+                for source in fs::files_with_extension(&crate_dir, "swift")? {
+                    sources.push((source, package));
+                }
+            }
+        }
+    }
+
+    reporter.phase_started(BuildPhase::Wrappers, sources.len());
+    for (source, package) in sources {
+        let prefix = SwiftWrapperPrefix {
+            ffi_modules: ffi_modules(project, package, layout),
+            internal_modules: uniffi_dependency_modules(project, package),
+            external_modules: external_modules(package),
+            extra_imports: &package.extra_imports,
+            external_types: &package.external_types,
+        }
+        .render()
+        .context("Can't render swift wrapper prefix")?;
+        let contents =
+            std::fs::read_to_string(&source).with_context(|| format!("Can't read {source}"))?;
+        let access = project.bindings_access_level.as_str();
+        let mut updated = update_swift_wrapper(&contents, &prefix, access);
+        // uniffi's checksum verification is file-private and only runs when
+        // the generated API is first used; this shim gives the smoke tests
+        // (and anyone else) an explicit way to trigger it.
+        if updated.contains("func uniffiEnsureInitialized()") {
+            updated.push_str(&verify_contract_shim(access));
+        }
+
+        let module_dir = wrapper_dir.join(&package.internal_module_name);
+        std::fs::create_dir_all(&module_dir)
+            .with_context(|| format!("Can't create {module_dir}"))?;
+        let destination = module_dir.join(source.file_name().unwrap());
+        std::fs::write(&destination, &updated)
+            .with_context(|| format!("Can't write {destination}"))?;
+        reporter.step_finished(BuildPhase::Wrappers, destination.to_string());
+    }
+
+    // Plugins run before the checksums are recorded, so their rewrites and
+    // additions count as generated output rather than manual edits.
+    run_post_generation_plugins(project, &wrapper_dir)?;
+    write_wrapper_checksums(&wrapper_dir)?;
+    reporter.phase_finished(BuildPhase::Wrappers);
+
+    Ok(())
+}
+
+/// Appended to each generated source defining `uniffiEnsureInitialized`,
+/// which is `private` and therefore callable from the same file only. The
+/// shim takes the same access level as the rest of the bindings.
+fn verify_contract_shim(access: &str) -> String {
+    format!(
+        "\n\
+        /// Runs uniffi's contract-version and API-checksum verification, which\n\
+        /// traps when these bindings don't match the compiled Rust library.\n\
+        /// Cheap, idempotent, and called by the generated smoke tests.\n\
+        {access} func uniffiVerifyContract() {{\n\
+        \x20   uniffiEnsureInitialized()\n\
+        }}\n"
+    )
+}
+
+/// Invoke each configured `post_generation_plugins` executable with the path
+/// to a JSON description of the generated wrapper sources. Plugins may
+/// rewrite the listed files or add new ones next to them — e.g. to inject
+/// mock factories or preview helpers per generated type.
+fn run_post_generation_plugins(project: &Project, wrapper_dir: &Utf8Path) -> Result<()> {
+    if project.post_generation_plugins.is_empty() {
+        return Ok(());
+    }
+    let mut modules = Vec::new();
+    for package in &project.uniffi_packages {
+        let module_dir = wrapper_dir.join(&package.internal_module_name);
+        if !module_dir.exists() {
+            continue;
+        }
+        let files: Vec<String> = fs::files_with_extension(&module_dir, "swift")?
+            .iter()
+            .map(Utf8PathBuf::to_string)
+            .collect();
+        modules.push(serde_json::json!({
+            "package": package.package.name,
+            "module": package.internal_module_name,
+            "public_module": package.public_module_name,
+            "directory": module_dir.as_str(),
+            "files": files,
+        }));
+    }
+    let input = serde_json::json!({
+        "ffi_module_name": project.ffi_module_name,
+        "wrapper_dir": wrapper_dir.as_str(),
+        "modules": modules,
+    });
+
+    let tmp_dir = project.tmp_dir("plugin-input");
+    std::fs::create_dir_all(&tmp_dir).with_context(|| format!("Can't create {tmp_dir}"))?;
+    let input_path = tmp_dir.join("generated.json");
+    std::fs::write(&input_path, serde_json::to_string_pretty(&input)?)
+        .with_context(|| format!("Can't write {input_path}"))?;
+    for plugin in &project.post_generation_plugins {
+        Command::new(plugin)
+            .arg(&input_path)
+            .successful_output()
+            .with_context(|| format!("Post-generation plugin {plugin} failed"))?;
+    }
+    Ok(())
+}
+
+/// Record the checksum of every wrapper source currently on disk, the state
+/// [`check_manual_edits`] compares future runs against.
+fn write_wrapper_checksums(wrapper_dir: &Utf8Path) -> Result<()> {
+    let mut checksums = Vec::new();
+    for module_dir in fs::subdirs(wrapper_dir)? {
+        let module = module_dir
+            .file_name()
+            .expect("subdirectories always have a name");
+        for source in fs::files_with_extension(&module_dir, "swift")? {
+            let contents =
+                std::fs::read(&source).with_context(|| format!("Can't read {source}"))?;
+            checksums.push(format!(
+                "{:016x} {module}/{}",
+                crate::utils::fnv1a_64(&contents),
+                source.file_name().unwrap(),
+            ));
+        }
+    }
+    let checksum_path = wrapper_dir.join(CHECKSUM_FILE);
+    std::fs::write(&checksum_path, checksums.join("\n") + "\n")
+        .with_context(|| format!("Can't write {checksum_path}"))?;
+    Ok(())
+}
+
+/// Name of the checksum manifest written next to the generated wrappers.
+const CHECKSUM_FILE: &str = ".checksums";
+
+/// Compare the wrappers on disk against the checksums recorded when they were
+/// generated, so hand edits aren't silently clobbered by regeneration. Warns
+/// by default; fails in strict mode.
+fn check_manual_edits(wrapper_dir: &Utf8Path, strict: bool) -> Result<()> {
+    let Ok(recorded) = std::fs::read_to_string(wrapper_dir.join(CHECKSUM_FILE)) else {
+        return Ok(());
+    };
+    let mut modified = Vec::new();
+    for line in recorded.lines() {
+        let Some((checksum, relative_path)) = line.split_once(' ') else {
+            continue;
+        };
+        let path = wrapper_dir.join(relative_path);
+        let Ok(contents) = std::fs::read(&path) else {
+            continue; // Deleted files are regenerated anyway.
+        };
+        if format!("{:016x}", crate::utils::fnv1a_64(&contents)) != checksum {
+            modified.push(path);
+        }
+    }
+    if modified.is_empty() {
+        return Ok(());
+    }
+    for path in &modified {
+        eprintln!("Warning: {path} was modified since it was generated; regenerating discards those edits.");
+    }
+    if strict {
+        bail!(
+            "{} generated wrapper file(s) were modified by hand; \
+             move the changes into the Rust sources or the wrapper templates",
+            modified.len()
+        );
+    }
+    Ok(())
+}
+
+/// Post-process one uniffi-generated Swift file:
+///
+/// - prepend the import prefix, since the bindings compile as their own SPM
+///   target and need explicit imports of the FFI module;
+/// - `open class` becomes `{access} class`: we don't support subclassing
+///   generated types;
+/// - generated object protocols get an `AnyObject` constraint so consumers
+///   can hold weak references;
+/// - `public` declarations are rewritten to `access`, which is `package`
+///   when `bindings_access_level` hides the bindings from consumers of the
+///   generated package.
+fn update_swift_wrapper(contents: &str, prefix: &str, access: &str) -> String {
+    let mut result = String::from(prefix);
+    for line in contents.lines() {
+        result.push_str(&rewrite_declaration(line, access));
+        result.push('\n');
+    }
+    result
+}
+
+/// Apply the access-level and protocol transforms to one line. Matching
+/// whitespace-separated tokens rather than exact prefixes keeps the rewrites
+/// working when a uniffi upgrade changes indentation or spacing; real
+/// swift-syntax parsing would mean shelling out to a Swift tool, which isn't
+/// worth it for declarations this regular.
+fn rewrite_declaration(line: &str, access: &str) -> String {
+    let trimmed = line.trim_start();
+    let indent = &line[..line.len() - trimmed.len()];
+    if let Some(rest) = strip_token(trimmed, "open") {
+        if strip_token(rest, "class").is_some() {
+            return format!("{indent}{access} {rest}");
+        }
+    }
+    if let Some(rest) = strip_token(trimmed, "public") {
+        if let Some(after) = strip_token(rest, "protocol") {
+            // Unconstrained object protocols get an `AnyObject` bound; the
+            // constraint goes right before the opening brace.
+            if !after.contains(':') {
+                if let Some(brace) = rest.find('{') {
+                    let declaration = rest[..brace].trim_end();
+                    return format!("{indent}{access} {declaration}: AnyObject {}", &rest[brace..]);
+                }
+            }
+        }
+        return format!("{indent}{access} {rest}");
+    }
+    line.to_string()
+}
+
+/// Strip `token` plus the whitespace after it from the start of `line`, if
+/// the line begins with that exact token.
+fn strip_token<'a>(line: &'a str, token: &str) -> Option<&'a str> {
+    let rest = line.strip_prefix(token)?;
+    if rest.starts_with(char::is_whitespace) {
+        Some(rest.trim_start())
+    } else {
+        None
+    }
+}
+
+/// Internal module names of the UniFFI packages that `package` depends on.
+fn uniffi_dependency_modules(project: &Project, package: &UniffiPackage) -> Vec<String> {
+    project
+        .uniffi_packages
+        .iter()
+        .filter(|other| {
+            package
+                .package
+                .dependencies
+                .iter()
+                .any(|d| d.name == *other.package.name)
+        })
+        .map(|other| other.internal_module_name.clone())
+        .collect()
+}
+
+pub(crate) fn relative_to_root(project: &Project, path: &Utf8Path) -> String {
+    path.strip_prefix(project.workspace_root())
+        .map(|p| p.to_string())
+        .unwrap_or_else(|_| path.to_string())
+}
+
+#[derive(Template)]
+#[template(path = "Package.swift", escape = "none")]
+struct PackageManifest {
+    name: String,
+    tools_version: String,
+    language_version: Option<String>,
+    /// Emit the `UNIFFI_LINKAGE` preamble and interpolate the chosen variant
+    /// into the binary target path, when both variants were packaged.
+    linkage_selection: bool,
+    /// `(package name, commit hash)` for every git-sourced UniFFI package.
+    pinned_revisions: Vec<(String, String)>,
+    platforms: Vec<String>,
+    products: Vec<SwiftProduct>,
+    targets: Vec<SwiftTarget>,
+}
+
+#[derive(Template)]
+#[template(path = "swift_wrapper_prefix.swift", escape = "none")]
+struct SwiftWrapperPrefix<'a> {
+    ffi_modules: Vec<String>,
+    internal_modules: Vec<String>,
+    external_modules: Vec<String>,
+    extra_imports: &'a [String],
+    external_types: &'a [ExternalType],
+}
+
+/// The clang FFI modules one package's wrapper sources import: the single
+/// merged module, or — per-crate — the crate's own module plus those of its
+/// UniFFI dependencies, whose low-level types the generated code references
+/// when lowering external types across crate boundaries.
+fn ffi_modules(project: &Project, package: &UniffiPackage, layout: FrameworkLayout) -> Vec<String> {
+    match layout {
+        FrameworkLayout::Merged => vec![project.ffi_module_name.clone()],
+        FrameworkLayout::PerCrate => {
+            let mut modules = vec![package.ffi_module_name()];
+            for dependency in project.uniffi_packages.iter().filter(|other| {
+                package
+                    .package
+                    .dependencies
+                    .iter()
+                    .any(|d| d.name == *other.package.name)
+            }) {
+                modules.push(dependency.ffi_module_name());
+            }
+            modules
+        }
+    }
+}
+
+/// The distinct Swift modules hosting the package's declared external types.
+fn external_modules(package: &UniffiPackage) -> Vec<String> {
+    let mut modules: Vec<String> = package
+        .external_types
+        .iter()
+        .map(|t| t.module.clone())
+        .collect();
+    modules.sort();
+    modules.dedup();
+    modules
+}
+
+/// One `.library(...)` entry in the generated manifest's products array.
+struct SwiftProduct {
+    name: String,
+    targets: Vec<String>,
+}
+
+impl fmt::Display for SwiftProduct {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let targets = self
+            .targets
+            .iter()
+            .map(|t| format!("\"{t}\""))
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(f, ".library(name: \"{}\", targets: [{targets}])", self.name)
+    }
+}
+
+pub(crate) enum SwiftTargetKind {
+    Binary,
+    Target,
+    TestTarget,
+}
+
+pub(crate) struct SwiftTarget {
+    pub(crate) name: String,
+    pub(crate) kind: SwiftTargetKind,
+    pub(crate) path: String,
+    pub(crate) dependencies: Vec<String>,
+    /// Rendered `swiftSettings` entries, e.g. `.define("FOO")`. Filled in
+    /// from `[swift_settings]` after all targets are assembled.
+    pub(crate) settings: Vec<String>,
+    /// Rendered `linkerSettings` entries, e.g. `.linkedLibrary("sqlite3")`.
+    /// Only the bindings targets carry these: they are what links the FFI
+    /// binary into the consumer.
+    pub(crate) linker_settings: Vec<String>,
+}
+
+impl fmt::Display for SwiftTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            SwiftTargetKind::Binary => {
+                write!(f, ".binaryTarget(name: \"{}\", path: \"{}\")", self.name, self.path)
+            }
+            SwiftTargetKind::Target | SwiftTargetKind::TestTarget => {
+                let function = match self.kind {
+                    SwiftTargetKind::TestTarget => ".testTarget",
+                    _ => ".target",
+                };
+                let dependencies = self
+                    .dependencies
+                    .iter()
+                    .map(|d| format!("\"{d}\""))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(
+                    f,
+                    "{function}(name: \"{}\", dependencies: [{dependencies}], path: \"{}\"",
+                    self.name, self.path
+                )?;
+                if !self.settings.is_empty() {
+                    write!(f, ", swiftSettings: [{}]", self.settings.join(", "))?;
+                }
+                if !self.linker_settings.is_empty() {
+                    write!(f, ", linkerSettings: [{}]", self.linker_settings.join(", "))?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update_swift_wrapper_rewrites_access_levels() {
+        let input = "open class Foo {\n}\npublic protocol FooProtocol {\n}\n";
+        let output = update_swift_wrapper(input, "import FFI\n", "public");
+        assert_eq!(
+            output,
+            "import FFI\npublic class Foo {\n}\npublic protocol FooProtocol: AnyObject {\n}\n"
+        );
+    }
+
+    #[test]
+    fn update_swift_wrapper_keeps_constrained_protocols() {
+        let input = "public protocol FooProtocol: AnyObject {\n";
+        let output = update_swift_wrapper(input, "", "public");
+        assert_eq!(output, input.to_owned());
+    }
+
+    #[test]
+    fn update_swift_wrapper_tolerates_whitespace_changes() {
+        let input = "open  class Foo {\n}\n    public protocol FooProtocol  {\n";
+        let output = update_swift_wrapper(input, "", "public");
+        assert_eq!(
+            output,
+            "public class Foo {\n}\n    public protocol FooProtocol: AnyObject {\n"
+        );
+    }
+
+    #[test]
+    fn wrapper_prefix_imports_every_ffi_module() {
+        let prefix = SwiftWrapperPrefix {
+            ffi_modules: vec!["ApiFFI".to_string(), "CoreFFI".to_string()],
+            internal_modules: vec!["Core".to_string()],
+            external_modules: Vec::new(),
+            extra_imports: &["Combine".to_string()],
+            external_types: &[],
+        }
+        .render()
+        .unwrap();
+        assert!(prefix.contains("#if canImport(ApiFFI)\n    import ApiFFI\n#endif"));
+        assert!(prefix.contains("#if canImport(CoreFFI)\n    import CoreFFI\n#endif"));
+        assert!(prefix.contains("\nimport Core"));
+        assert!(prefix.contains("\nimport Combine"));
+    }
+
+    #[test]
+    fn update_swift_wrapper_applies_package_access() {
+        let input = "open class Foo {\n}\npublic func bar() {\n}\ninternal func baz() {\n}\n";
+        let output = update_swift_wrapper(input, "", "package");
+        assert_eq!(
+            output,
+            "package class Foo {\n}\npackage func bar() {\n}\ninternal func baz() {\n}\n"
+        );
+    }
+
+    #[test]
+    fn swift_target_rendering() {
+        let target = SwiftTarget {
+            name: "Api".to_string(),
+            kind: SwiftTargetKind::Target,
+            path: "native/swift/Sources/Api".to_string(),
+            dependencies: vec!["ApiInternal".to_string()],
+            settings: Vec::new(),
+            linker_settings: Vec::new(),
+        };
+        assert_eq!(
+            target.to_string(),
+            ".target(name: \"Api\", dependencies: [\"ApiInternal\"], path: \"native/swift/Sources/Api\")"
+        );
+    }
+
+    #[test]
+    fn swift_target_rendering_with_settings() {
+        let target = SwiftTarget {
+            name: "Api".to_string(),
+            kind: SwiftTargetKind::Target,
+            path: "native/swift/Sources/Api".to_string(),
+            dependencies: Vec::new(),
+            settings: vec![
+                ".swiftLanguageVersion(.version(\"6\"))".to_string(),
+                ".define(\"DEBUG_FFI\")".to_string(),
+            ],
+            linker_settings: Vec::new(),
+        };
+        assert_eq!(
+            target.to_string(),
+            ".target(name: \"Api\", dependencies: [], path: \"native/swift/Sources/Api\", \
+             swiftSettings: [.swiftLanguageVersion(.version(\"6\")), .define(\"DEBUG_FFI\")])"
+        );
+    }
+
+    #[test]
+    fn swift_target_rendering_with_linker_settings() {
+        let target = SwiftTarget {
+            name: "ApiInternal".to_string(),
+            kind: SwiftTargetKind::Target,
+            path: "target/FFI/swift-wrapper/ApiInternal".to_string(),
+            dependencies: vec!["FFI".to_string()],
+            settings: Vec::new(),
+            linker_settings: vec![
+                ".linkedLibrary(\"sqlite3\")".to_string(),
+                ".linkedFramework(\"Security\")".to_string(),
+            ],
+        };
+        assert_eq!(
+            target.to_string(),
+            ".target(name: \"ApiInternal\", dependencies: [\"FFI\"], \
+             path: \"target/FFI/swift-wrapper/ApiInternal\", \
+             linkerSettings: [.linkedLibrary(\"sqlite3\"), .linkedFramework(\"Security\")])"
+        );
+    }
+}
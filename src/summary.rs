@@ -0,0 +1,78 @@
+//! End-of-run build summaries.
+//!
+//! A full multi-platform build prints pages of subprocess output; the
+//! summary condenses what actually happened — per-target status, build
+//! time, artifact size, bindings cache hits, and where the outputs went —
+//! into one table at the end, or a JSON document for scripted callers.
+
+use anyhow::Result;
+use camino::Utf8PathBuf;
+use serde::Serialize;
+
+/// One target triple's outcome.
+#[derive(Serialize)]
+pub(crate) struct TargetSummary {
+    pub(crate) target: String,
+    /// `"ok"` or `"failed"`.
+    pub(crate) status: &'static str,
+    /// Wall-clock time of the target's cargo builds; absent when the
+    /// rust-build stage was skipped.
+    pub(crate) duration_secs: Option<f64>,
+    /// Total size of the target's built static libraries, in bytes.
+    pub(crate) artifact_bytes: Option<u64>,
+    /// Whether the Swift bindings were reused (`"hit"`) or regenerated
+    /// (`"miss"`); absent when the bindings stage was skipped.
+    pub(crate) bindings_cache: Option<&'static str>,
+}
+
+/// What one build run produced, for the end-of-run report.
+#[derive(Default, Serialize)]
+pub(crate) struct BuildSummary {
+    pub(crate) targets: Vec<TargetSummary>,
+    /// The assembled XCFramework paths.
+    pub(crate) outputs: Vec<Utf8PathBuf>,
+}
+
+impl BuildSummary {
+    /// Print the summary as a console table, or as JSON with `json`.
+    pub(crate) fn print(&self, json: bool) -> Result<()> {
+        if json {
+            println!("{}", serde_json::to_string_pretty(self)?);
+            return Ok(());
+        }
+        let width = self
+            .targets
+            .iter()
+            .map(|target| target.target.len())
+            .max()
+            .unwrap_or_default()
+            .max("Target".len());
+        println!();
+        println!(
+            "{:width$}  {:6}  {:>8}  {:>9}  Bindings",
+            "Target", "Status", "Time", "Size"
+        );
+        for target in &self.targets {
+            let duration = match target.duration_secs {
+                Some(secs) => format!("{secs:.1}s"),
+                None => "-".to_string(),
+            };
+            let size = match target.artifact_bytes {
+                Some(bytes) => indicatif::HumanBytes(bytes).to_string(),
+                None => "-".to_string(),
+            };
+            println!(
+                "{:width$}  {:6}  {:>8}  {:>9}  {}",
+                target.target,
+                target.status,
+                duration,
+                size,
+                target.bindings_cache.unwrap_or("-"),
+            );
+        }
+        for output in &self.outputs {
+            println!("Output: {output}");
+        }
+        Ok(())
+    }
+}
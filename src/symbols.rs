@@ -0,0 +1,159 @@
+//! Symbol sanity checks for the merged static libraries.
+//!
+//! Two cargo packages can both vendor the same C library (sqlite is the
+//! classic case), which surfaces as duplicate strong symbols only when the
+//! consumer's app links the XCFramework. Similarly, a vendored C dependency
+//! can reference symbols nothing provides. Checking each merged archive with
+//! `nm` before `-create-xcframework` turns those obscure link errors into a
+//! readable report at packaging time.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use camino::Utf8Path;
+
+use crate::utils::ExecuteCommand;
+
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct SymbolReport {
+    /// External, strongly-defined symbols that appear in more than one object.
+    pub(crate) duplicates: Vec<String>,
+    /// Undefined symbols that neither the archive itself nor the usual system
+    /// libraries are expected to provide.
+    pub(crate) undefined: Vec<String>,
+}
+
+impl SymbolReport {
+    fn is_clean(&self) -> bool {
+        self.duplicates.is_empty() && self.undefined.is_empty()
+    }
+}
+
+/// Fail with a readable report when `library` contains duplicate strong
+/// symbols or suspicious undefined symbols.
+pub(crate) fn check_merged_library(library: &Utf8Path) -> Result<()> {
+    let output = Command::new("nm")
+        .arg(library.as_str())
+        .successful_output()?;
+    let stdout = String::from_utf8(output.stdout).context("nm produced non-UTF-8 output")?;
+    let report = analyze_nm_output(&stdout);
+    if report.is_clean() {
+        return Ok(());
+    }
+
+    let mut message = format!("Symbol check failed for {library}:\n");
+    if !report.duplicates.is_empty() {
+        message.push_str("  Duplicate strong symbols (two packages vendoring the same code?):\n");
+        for symbol in &report.duplicates {
+            message.push_str(&format!("    {symbol}\n"));
+        }
+    }
+    if !report.undefined.is_empty() {
+        message.push_str("  Undefined symbols no system library is expected to provide:\n");
+        for symbol in &report.undefined {
+            message.push_str(&format!("    {symbol}\n"));
+        }
+    }
+    bail!(message.trim_end().to_string())
+}
+
+/// Interpret plain `nm` output (one line per symbol, grouped by object file).
+pub(crate) fn analyze_nm_output(output: &str) -> SymbolReport {
+    let mut defined_count: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut undefined: BTreeSet<&str> = BTreeSet::new();
+
+    for line in output.lines() {
+        // Archive member headers look like `libfoo.a(bar.o):`.
+        let mut fields = line.split_whitespace().peekable();
+        let Some(first) = fields.next() else { continue };
+        let (symbol_type, name) = if first == "U" {
+            // Undefined symbols have no address column.
+            let Some(name) = fields.next() else { continue };
+            ("U", name)
+        } else {
+            let (Some(symbol_type), Some(name)) = (fields.next(), fields.next()) else {
+                continue;
+            };
+            (symbol_type, name)
+        };
+        match symbol_type {
+            // External, strongly defined (weak symbols like `W`/`V` may
+            // legitimately repeat).
+            "T" | "D" | "B" | "S" => {
+                *defined_count.entry(name).or_default() += 1;
+            }
+            "U" => {
+                undefined.insert(name);
+            }
+            _ => {}
+        }
+    }
+
+    let duplicates: Vec<String> = defined_count
+        .iter()
+        .filter(|(_, &count)| count > 1)
+        .map(|(name, _)| name.to_string())
+        .collect();
+    let undefined = undefined
+        .into_iter()
+        .filter(|name| !defined_count.contains_key(name) && !is_system_symbol(name))
+        .map(str::to_string)
+        .collect();
+
+    SymbolReport {
+        duplicates,
+        undefined,
+    }
+}
+
+/// Whether an undefined symbol is expected to be resolved by the OS runtime
+/// (libSystem, libc++, the Objective-C and Swift runtimes, CoreFoundation).
+fn is_system_symbol(name: &str) -> bool {
+    const SYSTEM_PREFIXES: &[&str] = &[
+        "_objc_", "_OBJC_", "_swift_", "_$s", "_os_", "_dispatch_", "_pthread_", "_mach_",
+        "_CF", "_Sec", "_SSL", "_kCF", "__ZSt", "__ZNSt", "__Unwind", "___stack_chk",
+        "___error", "___cxa", "_dyld_", "__tlv_bootstrap",
+    ];
+    // Plain libc symbols (`_malloc`, `_memcpy`, …) have no further underscore
+    // after the leading one; vendored C library symbols are typically
+    // namespaced (`_sqlite3_open`).
+    SYSTEM_PREFIXES.iter().any(|p| name.starts_with(p))
+        || (name.starts_with('_') && !name[1..].contains('_'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_duplicate_strong_symbols() {
+        let output = "\
+libfoo.a(a.o):
+0000000000000000 T _sqlite3_open
+libfoo.a(b.o):
+0000000000000000 T _sqlite3_open
+0000000000000010 W _weak_symbol
+libfoo.a(c.o):
+0000000000000020 W _weak_symbol
+";
+        let report = analyze_nm_output(output);
+        assert_eq!(report.duplicates, vec!["_sqlite3_open".to_string()]);
+        assert!(report.undefined.is_empty());
+    }
+
+    #[test]
+    fn reports_unexpected_undefined_symbols() {
+        let output = "\
+libfoo.a(a.o):
+0000000000000000 T _wp_api_func
+                 U _malloc
+                 U _objc_msgSend
+                 U _some_vendored_dep
+                 U _wp_api_func
+";
+        let report = analyze_nm_output(output);
+        assert!(report.duplicates.is_empty());
+        assert_eq!(report.undefined, vec!["_some_vendored_dep".to_string()]);
+    }
+}
@@ -0,0 +1,80 @@
+//! Tuist integration output.
+//!
+//! Host apps built with Tuist describe dependencies in Swift instead of
+//! editing an Xcode project, and re-declaring our XCFramework and wrapper
+//! products by hand drifts every release. The `generate-tuist` subcommand
+//! emits a `ProjectDescription` helper enumerating both, ready to be added
+//! to a target's dependencies.
+
+use anyhow::{Context, Result};
+use camino::Utf8Path;
+use rinja::Template;
+
+use crate::project::Project;
+use crate::xcframework::FrameworkLayout;
+
+/// Generate a Tuist `ProjectDescription` helper listing the SDK's wrapper
+/// products and XCFramework paths, at `output` or the default
+/// `Tuist/Dependencies.swift` under the workspace root.
+pub fn generate_tuist(layout: FrameworkLayout, output: Option<&Utf8Path>) -> crate::Result<()> {
+    let run = || -> Result<()> {
+        let project = Project::from_current_dir()?;
+
+        let xcframeworks: Vec<String> = match layout {
+            FrameworkLayout::Merged => {
+                vec![crate::spm::relative_to_root(
+                    &project,
+                    &project.xcframework_path(),
+                )]
+            }
+            FrameworkLayout::PerCrate => project
+                .uniffi_packages
+                .iter()
+                .map(|package| {
+                    crate::spm::relative_to_root(&project, &project.crate_xcframework_path(package))
+                })
+                .collect(),
+        };
+        let products: Vec<String> = project
+            .uniffi_packages
+            .iter()
+            .map(|package| package.public_module_name.clone())
+            .collect();
+
+        let rendered = TuistDependencies {
+            name: &project.ffi_module_name,
+            package_path: ".",
+            products: &products,
+            xcframeworks: &xcframeworks,
+        }
+        .render()
+        .context("Can't render the Tuist dependencies helper")?;
+
+        let output_path = match output {
+            Some(path) => path.to_owned(),
+            None => project
+                .workspace_root()
+                .join("Tuist")
+                .join("Dependencies.swift"),
+        };
+        if let Some(parent) = output_path.parent() {
+            std::fs::create_dir_all(parent).with_context(|| format!("Can't create {parent}"))?;
+        }
+        std::fs::write(&output_path, rendered)
+            .with_context(|| format!("Can't write {output_path}"))?;
+        println!("Generated {output_path}");
+        Ok(())
+    };
+    run().map_err(crate::Error::from)
+}
+
+#[derive(Template)]
+#[template(path = "TuistDependencies.swift", escape = "none")]
+struct TuistDependencies<'a> {
+    name: &'a str,
+    /// Where the wrapper package lives, relative to the Tuist project. The
+    /// generated file sits inside the SDK repo, so this is always `.`.
+    package_path: &'a str,
+    products: &'a [String],
+    xcframeworks: &'a [String],
+}
@@ -0,0 +1,579 @@
+use std::io::BufRead;
+use std::process::{Command, ExitStatus, Output, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use camino::Utf8Path;
+
+use crate::error::Error;
+
+/// Whether subprocess output is streamed to the terminal as it is produced,
+/// instead of only surfacing on failure. Set from the CLI's `--verbose` flag.
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+/// Whether commands are only printed, not run. Set from `--dry-run`.
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
+/// Per-command timeout in milliseconds; 0 means none.
+static TIMEOUT_MS: AtomicU64 = AtomicU64::new(0);
+/// Whether cargo runs with `--offline`, for hermetic/airgapped builds. Set
+/// from the CLI's `--offline` flag.
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+/// Whether cargo runs with `--locked`, failing if Cargo.lock would change.
+static CARGO_LOCKED: AtomicBool = AtomicBool::new(false);
+/// Whether cargo runs with `--frozen` (implies `--locked` and `--offline`).
+static CARGO_FROZEN: AtomicBool = AtomicBool::new(false);
+/// Whether to use standalone LLVM tools (`llvm-lipo`) instead of going
+/// through `xcrun`, for hosts without an Xcode installation. Set by the
+/// zigbuild cross-compilation mode.
+static USE_LLVM_TOOLS: AtomicBool = AtomicBool::new(false);
+/// File receiving a timestamped record of every executed command and its
+/// full output, independent of console verbosity. Set from `--log-file`.
+static LOG_FILE: std::sync::Mutex<Option<camino::Utf8PathBuf>> = std::sync::Mutex::new(None);
+/// SDK version the builds should link against (e.g. `18.0` for a beta SDK),
+/// instead of the selected Xcode's default. Set from `--sdk-version`.
+static SDK_VERSION: std::sync::Mutex<Option<String>> = std::sync::Mutex::new(None);
+
+/// Enable or disable live streaming of subprocess output.
+pub fn set_verbose(enabled: bool) {
+    VERBOSE.store(enabled, Ordering::Relaxed);
+}
+
+/// When enabled, every command is printed instead of executed and reported as
+/// succeeding with empty output.
+pub fn set_dry_run(enabled: bool) {
+    DRY_RUN.store(enabled, Ordering::Relaxed);
+}
+
+/// Kill any command that runs longer than `timeout` and report it as failed.
+pub fn set_command_timeout(timeout: Option<Duration>) {
+    TIMEOUT_MS.store(
+        timeout.map(|t| t.as_millis() as u64).unwrap_or(0),
+        Ordering::Relaxed,
+    );
+}
+
+pub(crate) fn dry_run() -> bool {
+    DRY_RUN.load(Ordering::Relaxed)
+}
+
+/// When enabled, every cargo invocation (including `cargo metadata`) runs
+/// with `--offline`, and operations that would need to re-fetch sources fail
+/// instead.
+pub fn set_offline(enabled: bool) {
+    OFFLINE.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// When enabled, every cargo invocation (including `cargo metadata`) runs
+/// with `--locked`, so a Cargo.lock that would change fails the build
+/// instead of silently resolving new dependency versions.
+pub fn set_cargo_locked(enabled: bool) {
+    CARGO_LOCKED.store(enabled, Ordering::Relaxed);
+}
+
+/// When enabled, every cargo invocation runs with `--frozen`, which refuses
+/// both lockfile changes and network access.
+pub fn set_cargo_frozen(enabled: bool) {
+    CARGO_FROZEN.store(enabled, Ordering::Relaxed);
+}
+
+/// The `--locked`/`--frozen` flags every cargo invocation must carry.
+pub(crate) fn cargo_lock_flags() -> Vec<&'static str> {
+    let mut flags = Vec::new();
+    if CARGO_FROZEN.load(Ordering::Relaxed) {
+        flags.push("--frozen");
+    } else if CARGO_LOCKED.load(Ordering::Relaxed) {
+        flags.push("--locked");
+    }
+    flags
+}
+
+/// Prefer standalone LLVM tools over `xcrun` wrappers.
+pub(crate) fn set_use_llvm_tools(enabled: bool) {
+    USE_LLVM_TOOLS.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn use_llvm_tools() -> bool {
+    USE_LLVM_TOOLS.load(Ordering::Relaxed)
+}
+
+/// The configured replacement for `tool`, from the `UNIFFI_<TOOL>`
+/// environment variable (uppercased, dashes mapped to underscores — e.g.
+/// `UNIFFI_LIPO`, `UNIFFI_LLVM_COV`). Lets slimmed-down CI images and
+/// cross-build environments substitute their own binaries for the Apple
+/// toolchain ones.
+pub(crate) fn tool_override(tool: &str) -> Option<Command> {
+    let env_var = format!("UNIFFI_{}", tool.to_uppercase().replace('-', "_"));
+    std::env::var(env_var).ok().map(Command::new)
+}
+
+/// `tool` located through `xcrun`, unless `UNIFFI_<TOOL>` names a
+/// replacement (see [`tool_override`]).
+pub(crate) fn xcrun_tool(tool: &str) -> Command {
+    tool_override(tool).unwrap_or_else(|| {
+        let mut cmd = Command::new("xcrun");
+        cmd.arg(tool);
+        cmd
+    })
+}
+
+/// `xcodebuild` from `PATH`, unless `UNIFFI_XCODEBUILD` names a replacement.
+pub(crate) fn xcodebuild_command() -> Command {
+    tool_override("xcodebuild").unwrap_or_else(|| Command::new("xcodebuild"))
+}
+
+/// Build against this SDK version (e.g. a beta SDK's `26.0`) instead of the
+/// selected Xcode's default: cargo builds get a matching `SDKROOT`, and
+/// xcodebuild invocations a `-sdk` argument. The default Xcode selection
+/// stays untouched system-wide.
+pub fn set_sdk_version(version: &str) {
+    *SDK_VERSION.lock().expect("SDK version lock poisoned") = Some(version.to_string());
+}
+
+pub(crate) fn sdk_version() -> Option<String> {
+    SDK_VERSION.lock().expect("SDK version lock poisoned").clone()
+}
+
+/// Append every executed command, with its exit status and both output
+/// streams, to the file at `path`. The console stays as quiet (or verbose)
+/// as before; the log exists so CI failures are diagnosable after the fact.
+pub fn set_log_file(path: &Utf8Path) {
+    *LOG_FILE.lock().expect("log file lock poisoned") = Some(path.to_path_buf());
+}
+
+pub(crate) fn log_file() -> Option<camino::Utf8PathBuf> {
+    LOG_FILE.lock().expect("log file lock poisoned").clone()
+}
+
+/// Append one entry to the command log, if one is configured. Logging
+/// failures are swallowed: a broken log must not fail the build it records.
+fn append_log(entry: &str) {
+    let Some(path) = log_file() else {
+        return;
+    };
+    use std::io::Write;
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    if let Ok(mut file) = std::fs::OpenOptions::new().create(true).append(true).open(path) {
+        let _ = writeln!(file, "[{timestamp}] {entry}");
+    }
+}
+
+/// Convenience for running a [`Command`] through the global [`CommandRunner`]
+/// and treating a non-zero exit status as an error carrying the captured
+/// stderr.
+pub(crate) trait ExecuteCommand {
+    fn successful_output(&mut self) -> Result<Output>;
+}
+
+impl ExecuteCommand for Command {
+    fn successful_output(&mut self) -> Result<Output> {
+        CommandRunner::global().run(self)
+    }
+}
+
+/// Central subprocess execution: one place for logging, dry-run, timeouts,
+/// and error formatting, so every module reports failures the same way.
+pub(crate) struct CommandRunner {
+    verbose: bool,
+    dry_run: bool,
+    timeout: Option<Duration>,
+}
+
+impl CommandRunner {
+    /// Snapshot of the process-wide settings configured by the CLI.
+    pub(crate) fn global() -> Self {
+        let timeout_ms = TIMEOUT_MS.load(Ordering::Relaxed);
+        Self {
+            verbose: VERBOSE.load(Ordering::Relaxed),
+            dry_run: DRY_RUN.load(Ordering::Relaxed),
+            timeout: (timeout_ms > 0).then(|| Duration::from_millis(timeout_ms)),
+        }
+    }
+
+    /// Run `cmd` to completion. Both output streams are always captured for
+    /// error reporting; in verbose mode they are additionally forwarded line
+    /// by line, prefixed with the program name, so long-running tools like
+    /// xcodebuild show progress instead of looking hung.
+    pub(crate) fn run(&self, cmd: &mut Command) -> Result<Output> {
+        if self.verbose || self.dry_run {
+            eprintln!("$ {:?}", cmd);
+        }
+        if self.dry_run {
+            append_log(&format!("$ {cmd:?} (dry run)"));
+            return Ok(Output {
+                status: success_status(),
+                stdout: Vec::new(),
+                stderr: Vec::new(),
+            });
+        }
+        append_log(&format!("$ {cmd:?}"));
+
+        let prefix = Utf8Path::new(&cmd.get_program().to_string_lossy().into_owned())
+            .file_name()
+            .unwrap_or("?")
+            .to_string();
+        let mut child = cmd
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Failed to execute {:?}", cmd))?;
+
+        let stdout = forward_stream(
+            child.stdout.take().expect("stdout was requested piped"),
+            self.verbose.then(|| prefix.clone()),
+            false,
+        );
+        let stderr = forward_stream(
+            child.stderr.take().expect("stderr was requested piped"),
+            self.verbose.then(|| prefix.clone()),
+            true,
+        );
+
+        let started = Instant::now();
+        let status = self.wait(&mut child, cmd).inspect_err(|error| {
+            append_log(&format!("failed: {error:#}"));
+        })?;
+        let output = Output {
+            status,
+            stdout: stdout.join().expect("stdout forwarding never panics"),
+            stderr: stderr.join().expect("stderr forwarding never panics"),
+        };
+        append_log(&format!(
+            "exit {status} after {:.1}s\n--- stdout ---\n{}--- stderr ---\n{}",
+            started.elapsed().as_secs_f64(),
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr),
+        ));
+        if !output.status.success() {
+            return Err(Error::CommandFailed {
+                cmd: format!("{:?}", cmd),
+                stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            }
+            .into());
+        }
+        Ok(output)
+    }
+
+    /// Wait for `child`, killing it when the configured timeout elapses.
+    fn wait(&self, child: &mut std::process::Child, cmd: &Command) -> Result<ExitStatus> {
+        let Some(timeout) = self.timeout else {
+            return child
+                .wait()
+                .with_context(|| format!("Failed to wait for {:?}", cmd));
+        };
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(status) = child
+                .try_wait()
+                .with_context(|| format!("Failed to wait for {:?}", cmd))?
+            {
+                return Ok(status);
+            }
+            if Instant::now() >= deadline {
+                child.kill().ok();
+                child.wait().ok();
+                return Err(Error::CommandFailed {
+                    cmd: format!("{:?}", cmd),
+                    stderr: format!("Timed out after {}s", timeout.as_secs()),
+                }
+                .into());
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+    }
+}
+
+/// Collect a child stream on a thread, optionally echoing each line with a
+/// `[program]` prefix as it arrives.
+fn forward_stream<R>(
+    pipe: R,
+    echo_prefix: Option<String>,
+    to_stderr: bool,
+) -> std::thread::JoinHandle<Vec<u8>>
+where
+    R: std::io::Read + Send + 'static,
+{
+    std::thread::spawn(move || {
+        // Collect raw bytes: the stream becomes `Output.stdout`/`stderr`,
+        // which the artifact parsing and error diagnostics consume, so a
+        // non-UTF-8 byte (e.g. a non-UTF-8 path in a linker error) must not
+        // truncate it. Only the echo is lossy-decoded.
+        let mut collected = Vec::new();
+        let mut reader = std::io::BufReader::new(pipe);
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            match reader.read_until(b'\n', &mut line) {
+                Ok(0) => break,
+                Ok(_) => {}
+                // Preserve whatever the read delivered before failing.
+                Err(_) => {
+                    collected.extend_from_slice(&line);
+                    break;
+                }
+            }
+            collected.extend_from_slice(&line);
+            if let Some(prefix) = &echo_prefix {
+                let text = String::from_utf8_lossy(&line);
+                let text = text.trim_end_matches('\n');
+                if to_stderr {
+                    eprintln!("[{prefix}] {text}");
+                } else {
+                    println!("[{prefix}] {text}");
+                }
+            }
+        }
+        collected
+    })
+}
+
+#[cfg(unix)]
+fn success_status() -> ExitStatus {
+    use std::os::unix::process::ExitStatusExt;
+    ExitStatus::from_raw(0)
+}
+
+/// 64-bit FNV-1a. Not cryptographic; just cheap change detection without
+/// pulling in a hashing dependency.
+pub(crate) fn fnv1a_64(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+/// Advisory lock around outputs shared between invocations (the assembled
+/// XCFramework, `Package.swift`). Scratch files use per-invocation unique
+/// paths instead, but the final outputs have fixed locations, so concurrent
+/// invocations sharing a workspace must take turns writing them.
+///
+/// The lock is a `create_new` file under `target/`, removed on drop. A crash
+/// can leave it behind; the wait message names the file so it can be deleted.
+pub(crate) struct WorkspaceLock {
+    path: camino::Utf8PathBuf,
+}
+
+impl WorkspaceLock {
+    pub(crate) fn acquire(target_dir: &Utf8Path) -> Result<Self> {
+        let path = target_dir.join("uniffi-swift-helper.lock");
+        let mut waited = false;
+        loop {
+            match std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(_) => return Ok(Self { path }),
+                Err(error) if error.kind() == std::io::ErrorKind::AlreadyExists => {
+                    if !waited {
+                        println!("Waiting for {path} held by another invocation…");
+                        waited = true;
+                    }
+                    std::thread::sleep(Duration::from_millis(200));
+                }
+                Err(error) => {
+                    return Err(error).with_context(|| format!("Can't create lock file {path}"))
+                }
+            }
+        }
+    }
+}
+
+impl Drop for WorkspaceLock {
+    fn drop(&mut self) {
+        std::fs::remove_file(&self.path).ok();
+    }
+}
+
+pub(crate) mod fs {
+    use std::fs;
+
+    use anyhow::{Context, Result};
+    use camino::{Utf8Path, Utf8PathBuf};
+
+    /// Delete `dir` if it exists and create it anew.
+    pub(crate) fn recreate_dir(dir: &Utf8Path) -> Result<()> {
+        if dir.exists() {
+            fs::remove_dir_all(dir).with_context(|| format!("Can't remove {dir}"))?;
+        }
+        fs::create_dir_all(dir).with_context(|| format!("Can't create {dir}"))?;
+        Ok(())
+    }
+
+    /// Copy `src` to `dst` without duplicating the bytes when possible.
+    ///
+    /// A hard link is instant and free on the same filesystem (on APFS it
+    /// behaves like a clone for our purposes: we never mutate copies in
+    /// place, only replace them wholesale). Falls back to a byte copy when
+    /// linking fails, e.g. across filesystems. Returns the file size.
+    pub(crate) fn clone_or_copy(src: &Utf8Path, dst: &Utf8Path) -> Result<u64> {
+        if dst.exists() {
+            fs::remove_file(dst).with_context(|| format!("Can't remove {dst}"))?;
+        }
+        if fs::hard_link(src, dst).is_ok() {
+            return Ok(src
+                .metadata()
+                .with_context(|| format!("Can't stat {src}"))?
+                .len());
+        }
+        fs::copy(src, dst).with_context(|| format!("Can't copy {src} to {dst}"))
+    }
+
+    /// Recursively copy the contents of `src` into `dst`, creating `dst` if needed.
+    pub(crate) fn copy_dir(src: &Utf8Path, dst: &Utf8Path) -> Result<()> {
+        copy_dir_excluding(src, dst, &[])
+    }
+
+    /// Like [`copy_dir`], but skip entries whose name matches one of
+    /// `excludes`, at any depth.
+    pub(crate) fn copy_dir_excluding(
+        src: &Utf8Path,
+        dst: &Utf8Path,
+        excludes: &[&str],
+    ) -> Result<()> {
+        fs::create_dir_all(dst).with_context(|| format!("Can't create {dst}"))?;
+        for entry in src
+            .read_dir_utf8()
+            .with_context(|| format!("Can't read {src}"))?
+        {
+            let entry = entry?;
+            if excludes.contains(&entry.file_name()) {
+                continue;
+            }
+            let dest = dst.join(entry.file_name());
+            if entry.file_type()?.is_dir() {
+                copy_dir_excluding(entry.path(), &dest, excludes)?;
+            } else {
+                clone_or_copy(entry.path(), &dest)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Non-recursively list the files in `dir` whose extension is `extension`.
+    pub(crate) fn files_with_extension(dir: &Utf8Path, extension: &str) -> Result<Vec<Utf8PathBuf>> {
+        let mut files = Vec::new();
+        for entry in dir
+            .read_dir_utf8()
+            .with_context(|| format!("Can't read {dir}"))?
+        {
+            let entry = entry?;
+            if entry.file_type()?.is_file() && entry.path().extension() == Some(extension) {
+                files.push(entry.path().to_path_buf());
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+
+    /// Non-recursively list the subdirectories of `dir`, sorted by name.
+    pub(crate) fn subdirs(dir: &Utf8Path) -> Result<Vec<Utf8PathBuf>> {
+        let mut subdirs = Vec::new();
+        for entry in dir
+            .read_dir_utf8()
+            .with_context(|| format!("Can't read {dir}"))?
+        {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                subdirs.push(entry.path().to_path_buf());
+            }
+        }
+        subdirs.sort();
+        Ok(subdirs)
+    }
+
+    /// A digest of a directory tree: every file's relative path and contents,
+    /// in sorted order. Two trees with the same digest have the same files.
+    /// Hidden entries and names in `excludes` are skipped, so bookkeeping
+    /// files (vendoring markers, `.DS_Store`) and excluded directories don't
+    /// affect the comparison.
+    pub(crate) fn tree_digest(dir: &Utf8Path, excludes: &[&str]) -> Result<u64> {
+        fn walk(
+            root: &Utf8Path,
+            dir: &Utf8Path,
+            excludes: &[&str],
+            input: &mut Vec<u8>,
+        ) -> Result<()> {
+            let mut entries: Vec<_> = dir
+                .read_dir_utf8()
+                .with_context(|| format!("Can't read {dir}"))?
+                .collect::<std::io::Result<_>>()?;
+            entries.sort_by(|a, b| a.path().cmp(b.path()));
+            for entry in entries {
+                if entry.file_name().starts_with('.') || excludes.contains(&entry.file_name()) {
+                    continue;
+                }
+                if entry.file_type()?.is_dir() {
+                    walk(root, entry.path(), excludes, input)?;
+                } else {
+                    let relative = entry
+                        .path()
+                        .strip_prefix(root)
+                        .expect("entries are always under the root");
+                    input.extend_from_slice(relative.as_str().as_bytes());
+                    input.extend_from_slice(
+                        &fs::read(entry.path())
+                            .with_context(|| format!("Can't read {}", entry.path()))?,
+                    );
+                }
+            }
+            Ok(())
+        }
+        let mut input = Vec::new();
+        walk(dir, dir, excludes, &mut input)?;
+        Ok(super::fnv1a_64(&input))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn files_with_extension_ignores_subdirs_and_other_extensions() {
+            let dir = tempfile::tempdir().unwrap();
+            let root = Utf8Path::from_path(dir.path()).unwrap();
+            fs::write(root.join("libfoo.a"), b"").unwrap();
+            fs::write(root.join("libfoo.d"), b"").unwrap();
+            fs::create_dir(root.join("deps.a")).unwrap();
+
+            let files = files_with_extension(root, "a").unwrap();
+            assert_eq!(files, vec![root.join("libfoo.a")]);
+        }
+
+        #[test]
+        fn clone_or_copy_replaces_existing_destination() {
+            let dir = tempfile::tempdir().unwrap();
+            let root = Utf8Path::from_path(dir.path()).unwrap();
+            let src = root.join("src");
+            let dst = root.join("dst");
+            fs::write(&src, b"new contents").unwrap();
+            fs::write(&dst, b"old").unwrap();
+
+            let bytes = clone_or_copy(&src, &dst).unwrap();
+            assert_eq!(bytes, 12);
+            assert_eq!(fs::read(&dst).unwrap(), b"new contents");
+        }
+
+        #[test]
+        fn subdirs_lists_only_directories_sorted() {
+            let dir = tempfile::tempdir().unwrap();
+            let root = Utf8Path::from_path(dir.path()).unwrap();
+            fs::create_dir(root.join("Two")).unwrap();
+            fs::create_dir(root.join("One")).unwrap();
+            fs::write(root.join("file"), b"").unwrap();
+
+            assert_eq!(
+                subdirs(root).unwrap(),
+                vec![root.join("One"), root.join("Two")]
+            );
+        }
+    }
+}
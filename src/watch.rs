@@ -0,0 +1,111 @@
+//! Rebuild-on-change mode for rapid iteration.
+//!
+//! Watches the Rust source directories of every UniFFI package and, whenever
+//! something changes, rebuilds the single slice for the requested platform,
+//! regenerates the bindings, and refreshes the swift-wrapper directory — so
+//! Swift developers see updated bindings seconds after a Rust edit, without
+//! paying for a full multi-platform build.
+
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use notify::{RecursiveMode, Watcher};
+
+use crate::build::{build_uniffi_package, generate_bindings, profile_dir_name, BuildOptions};
+use crate::error::Error;
+use crate::events::Reporter;
+use crate::project::Project;
+use crate::xcframework::FrameworkLayout;
+use crate::spm::update_swift_wrappers;
+use crate::xcframework::ApplePlatform;
+
+/// How long to keep collecting filesystem events before rebuilding, so one
+/// save that touches several files triggers a single rebuild.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Watch the workspace and rebuild the `platform` slice on every change.
+/// Runs until interrupted.
+pub fn watch(platform: ApplePlatform, profile: &str, reporter: &Reporter) -> crate::Result<()> {
+    watch_impl(platform, profile, reporter).map_err(Error::from)
+}
+
+fn watch_impl(platform: ApplePlatform, profile: &str, reporter: &Reporter) -> Result<()> {
+    let project = Project::from_current_dir()?;
+    // One slice is enough for the dev loop; the first triple is the native
+    // one on current hardware (arm64).
+    let target = platform.target_triples()[0];
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).context("Can't create file watcher")?;
+    for package in &project.uniffi_packages {
+        watcher
+            .watch(package.manifest_dir().as_std_path(), RecursiveMode::Recursive)
+            .with_context(|| format!("Can't watch {}", package.manifest_dir()))?;
+    }
+
+    println!("Watching for changes; rebuilding {target} ({profile}). Press Ctrl-C to stop.");
+    if let Err(error) = rebuild(&project, &platform, target, profile, reporter) {
+        eprintln!("Build failed: {error:#}");
+    }
+
+    loop {
+        // Block until something changes, then drain further events briefly.
+        let event = rx.recv().context("File watcher stopped")?;
+        let mut relevant = event.map(|e| e.paths).unwrap_or_default();
+        let deadline = Instant::now() + DEBOUNCE;
+        while let Ok(event) = rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            if let Ok(event) = event {
+                relevant.extend(event.paths);
+            }
+        }
+        if !relevant.iter().any(|path| is_relevant(&project, path)) {
+            continue;
+        }
+
+        println!("Change detected; rebuilding…");
+        match rebuild(&project, &platform, target, profile, reporter) {
+            Ok(()) => println!("Up to date. Watching for changes…"),
+            Err(error) => eprintln!("Build failed: {error:#}"),
+        }
+    }
+}
+
+/// Ignore changes in `target/` and the output root (our own outputs would
+/// retrigger forever) and in `.git`.
+fn is_relevant(project: &Project, path: &Path) -> bool {
+    !path.starts_with(project.target_dir().as_std_path())
+        && !path.starts_with(project.output_root().as_std_path())
+        && !path.components().any(|c| c.as_os_str() == ".git")
+}
+
+fn rebuild(
+    project: &Project,
+    platform: &ApplePlatform,
+    target: &str,
+    profile: &str,
+    reporter: &Reporter,
+) -> Result<()> {
+    for package in &project.uniffi_packages {
+        build_uniffi_package(
+            project,
+            package,
+            target,
+            *platform,
+            profile,
+            &BuildOptions::default(),
+            None,
+        )?;
+    }
+    let regenerated = generate_bindings(
+        project,
+        target,
+        profile_dir_name(profile),
+        &BuildOptions::default(),
+    )?;
+    if regenerated || !project.swift_wrapper_dir().exists() {
+        update_swift_wrappers(project, target, FrameworkLayout::default(), false, reporter)?;
+    }
+    Ok(())
+}
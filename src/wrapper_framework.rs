@@ -0,0 +1,96 @@
+//! Binary distribution of the Swift wrapper itself.
+//!
+//! The normal output ships the wrapper targets as sources and only the FFI
+//! libraries as a binary. Closed-source consumers instead want the wrapper
+//! compiled into its own XCFramework, built with
+//! `BUILD_LIBRARY_FOR_DISTRIBUTION=YES` so `.swiftinterface` files are
+//! emitted and the framework stays usable across Swift compiler versions.
+//! This layers on top of the FFI XCFramework produced by `build` and the
+//! generated `Package.swift`.
+
+
+use anyhow::{Context, Result};
+use camino::Utf8PathBuf;
+
+use crate::error::Error;
+use crate::project::Project;
+use crate::utils::{fs, ExecuteCommand};
+use crate::xcframework::ApplePlatform;
+
+/// Compile each public wrapper module into `target/<Module>.xcframework`
+/// with library evolution enabled.
+pub fn build_wrapper_xcframework(platforms: &[ApplePlatform]) -> crate::Result<()> {
+    build_wrapper_xcframework_impl(platforms).map_err(Error::from)
+}
+
+fn build_wrapper_xcframework_impl(platforms: &[ApplePlatform]) -> Result<()> {
+    let project = Project::from_current_dir()?;
+    if !project.workspace_root().join("Package.swift").exists() {
+        anyhow::bail!("Package.swift not found. Run `uniffi-swift-helper generate-package` first.");
+    }
+
+    let archives_dir = project.wrapper_archives_dir();
+    fs::recreate_dir(&archives_dir)?;
+
+    for package in &project.uniffi_packages {
+        let module = &package.public_module_name;
+        let mut frameworks = Vec::new();
+        for platform in platforms {
+            for destination in platform.archive_destinations() {
+                let archive = archives_dir.join(format!(
+                    "{module}-{}.xcarchive",
+                    destination.replace(['/', ' ', '='], "-")
+                ));
+                let mut cmd = crate::utils::xcodebuild_command();
+                cmd.args(["archive", "-scheme", module])
+                    .args(["-destination", destination])
+                    .args(["-archivePath", archive.as_str()])
+                    .args(["BUILD_LIBRARY_FOR_DISTRIBUTION=YES", "SKIP_INSTALL=NO"])
+                    .current_dir(project.workspace_root());
+                if let Some(version) = crate::utils::sdk_version() {
+                    if let Some(sdk) = sdk_for_destination(destination) {
+                        cmd.args(["-sdk", &format!("{sdk}{version}")]);
+                    }
+                }
+                cmd.successful_output()?;
+                frameworks.push(
+                    archive
+                        .join("Products")
+                        .join("Library")
+                        .join("Frameworks")
+                        .join(format!("{module}.framework")),
+                );
+            }
+        }
+
+        let output: Utf8PathBuf = project.module_xcframework_path(module);
+        if output.exists() {
+            std::fs::remove_dir_all(&output).with_context(|| format!("Can't remove {output}"))?;
+        }
+        let mut cmd = crate::utils::xcodebuild_command();
+        cmd.arg("-create-xcframework");
+        for framework in &frameworks {
+            cmd.args(["-framework", framework.as_str()]);
+        }
+        cmd.args(["-output", output.as_str()]);
+        cmd.successful_output()?;
+        println!("Created {output}");
+    }
+
+    Ok(())
+}
+
+/// The `xcodebuild -sdk` name matching an archive destination, used when
+/// `--sdk-version` pins the SDK instead of the destination's default.
+fn sdk_for_destination(destination: &str) -> Option<&'static str> {
+    match destination {
+        "generic/platform=iOS" => Some("iphoneos"),
+        "generic/platform=iOS Simulator" => Some("iphonesimulator"),
+        "generic/platform=macOS" => Some("macosx"),
+        "generic/platform=tvOS" => Some("appletvos"),
+        "generic/platform=tvOS Simulator" => Some("appletvsimulator"),
+        "generic/platform=watchOS" => Some("watchos"),
+        "generic/platform=watchOS Simulator" => Some("watchsimulator"),
+        _ => None,
+    }
+}
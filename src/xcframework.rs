@@ -0,0 +1,1201 @@
+use std::collections::BTreeMap;
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use rayon::prelude::*;
+
+use crate::build::{BuildOptions, CrateType};
+use crate::deployment::DeploymentTargets;
+use crate::events::{BuildPhase, Reporter};
+use crate::project::{ModulemapLayout, Project, UniffiPackage};
+use crate::utils::{fs, ExecuteCommand};
+
+/// The Apple platforms an XCFramework can contain slices for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum ApplePlatform {
+    #[value(name = "ios")]
+    IOS,
+    #[value(name = "macos")]
+    MacOS,
+    #[value(name = "tvos")]
+    TvOS,
+    #[value(name = "watchos")]
+    WatchOS,
+}
+
+impl ApplePlatform {
+    pub fn all() -> Vec<Self> {
+        vec![Self::IOS, Self::MacOS, Self::TvOS, Self::WatchOS]
+    }
+
+    /// The name used on the command line and in `[build_env]` sections.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Self::IOS => "ios",
+            Self::MacOS => "macos",
+            Self::TvOS => "tvos",
+            Self::WatchOS => "watchos",
+        }
+    }
+
+    /// The Rust target triples to build for this platform.
+    pub(crate) fn target_triples(&self) -> Vec<&'static str> {
+        match self {
+            Self::IOS => vec![
+                "aarch64-apple-ios",
+                "aarch64-apple-ios-sim",
+                "x86_64-apple-ios",
+            ],
+            Self::MacOS => vec!["aarch64-apple-darwin", "x86_64-apple-darwin"],
+            Self::TvOS => vec![
+                "aarch64-apple-tvos",
+                "aarch64-apple-tvos-sim",
+                "x86_64-apple-tvos",
+            ],
+            Self::WatchOS => vec![
+                "arm64_32-apple-watchos",
+                "aarch64-apple-watchos",
+                "aarch64-apple-watchos-sim",
+                "x86_64-apple-watchos-sim",
+            ],
+        }
+    }
+
+    /// tvOS and watchOS are tier-3 targets: they have no prebuilt std and
+    /// need `-Z build-std` on a nightly toolchain.
+    pub(crate) fn requires_nightly_toolchain(&self) -> bool {
+        matches!(self, Self::TvOS | Self::WatchOS)
+    }
+
+    /// The `xcodebuild -destination` values covering this platform's device
+    /// and simulator variants.
+    pub(crate) fn archive_destinations(&self) -> Vec<&'static str> {
+        match self {
+            Self::IOS => vec!["generic/platform=iOS", "generic/platform=iOS Simulator"],
+            Self::MacOS => vec!["generic/platform=macOS"],
+            Self::TvOS => vec!["generic/platform=tvOS", "generic/platform=tvOS Simulator"],
+            Self::WatchOS => vec![
+                "generic/platform=watchOS",
+                "generic/platform=watchOS Simulator",
+            ],
+        }
+    }
+}
+
+/// How the built libraries are packaged into XCFrameworks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum FrameworkLayout {
+    /// A single XCFramework containing the merged FFI module.
+    #[default]
+    Merged,
+    /// One XCFramework per UniFFI crate, each with its own headers and module
+    /// map, wired into Package.swift as separate binary targets. Lets
+    /// consumers depend on a lightweight sub-module instead of the whole FFI.
+    PerCrate,
+}
+
+/// One built library for a single target triple.
+pub(crate) struct Slice {
+    pub(crate) target_triple: String,
+    pub(crate) library_path: Utf8PathBuf,
+}
+
+impl Slice {
+    /// Locate the `crate_type` library cargo produced for `target_triple`.
+    pub(crate) fn create(
+        project: &Project,
+        target_triple: &str,
+        profile_dir_name: &str,
+        crate_type: CrateType,
+    ) -> Result<Self> {
+        let dir = project.target_dir().join(target_triple).join(profile_dir_name);
+        Ok(Self {
+            target_triple: target_triple.to_string(),
+            library_path: crate::build::find_library(&dir, None, crate_type)?,
+        })
+    }
+
+    /// Locate one crate's `crate_type` library for `target_triple`.
+    pub(crate) fn create_for_package(
+        project: &Project,
+        target_triple: &str,
+        profile_dir_name: &str,
+        package: &UniffiPackage,
+        crate_type: CrateType,
+    ) -> Result<Self> {
+        let dir = project.target_dir().join(target_triple).join(profile_dir_name);
+        Ok(Self {
+            target_triple: target_triple.to_string(),
+            library_path: crate::build::find_library(&dir, Some(package), crate_type)?,
+        })
+    }
+
+    /// The architecture name `lipo` reports for this slice's target triple.
+    fn expected_architecture(&self) -> &str {
+        let arch = self
+            .target_triple
+            .split('-')
+            .next()
+            .expect("split always yields at least one component");
+        match arch {
+            "aarch64" => "arm64",
+            other => other,
+        }
+    }
+
+    /// Check with `lipo -info` that the archive contains the architecture its
+    /// triple asked for, catching stale libraries left by previous builds.
+    pub(crate) fn validate_architecture(&self) -> Result<()> {
+        let output = lipo_command()
+            .args(["-info", self.library_path.as_str()])
+            .successful_output()?;
+        let info = String::from_utf8_lossy(&output.stdout);
+        let expected = self.expected_architecture();
+        if !info.split_whitespace().any(|arch| arch == expected) {
+            let dir = self
+                .library_path
+                .parent()
+                .expect("library paths always have a parent");
+            bail!(
+                "{} does not contain {expected} code (lipo reports: {}). \
+                 Is {dir} holding a stale library from a previous build?",
+                self.library_path,
+                info.trim(),
+            );
+        }
+        Ok(())
+    }
+}
+
+/// `lipo` via `xcrun`, or standalone `llvm-lipo` (same interface) on hosts
+/// without Xcode, e.g. Linux runners cross-compiling through zigbuild. A
+/// `UNIFFI_LIPO` override beats both.
+pub(crate) fn lipo_command() -> Command {
+    if crate::utils::use_llvm_tools() && crate::utils::tool_override("lipo").is_none() {
+        Command::new("llvm-lipo")
+    } else {
+        crate::utils::xcrun_tool("lipo")
+    }
+}
+
+fn libtool_command() -> Command {
+    if crate::utils::use_llvm_tools() && crate::utils::tool_override("libtool").is_none() {
+        Command::new("llvm-libtool-darwin")
+    } else {
+        crate::utils::xcrun_tool("libtool")
+    }
+}
+
+/// The prebuilt archives configured for `target_triple`: the platform-wide
+/// `[extra_archives]` section plus the triple-specific one.
+fn extra_archives_for(project: &Project, target_triple: &str) -> Vec<Utf8PathBuf> {
+    let platform = ApplePlatform::all()
+        .into_iter()
+        .find(|platform| platform.target_triples().contains(&target_triple));
+    let mut archives = Vec::new();
+    for section in platform
+        .iter()
+        .map(|platform| platform.name())
+        .chain([target_triple])
+    {
+        if let Some(paths) = project.extra_archives.get(section) {
+            archives.extend(paths.iter().cloned());
+        }
+    }
+    archives
+}
+
+/// The deployment targets consulted by [`stamp_build_version`], when the
+/// build both asked for the fix-up and pointed at a consumer project.
+fn build_version_targets(options: &BuildOptions) -> Result<Option<DeploymentTargets>> {
+    if !options.fix_build_version {
+        return Ok(None);
+    }
+    options
+        .deployment_targets_from
+        .as_deref()
+        .map(DeploymentTargets::from_path)
+        .transpose()
+}
+
+/// The `xcrun --sdk` name for a group's platform and simulator flavor.
+fn sdk_name(id: &LibraryGroupId) -> Result<&'static str> {
+    Ok(match (id.os.as_str(), id.simulator) {
+        ("macos", _) => "macosx",
+        ("ios", false) => "iphoneos",
+        ("ios", true) => "iphonesimulator",
+        ("tvos", false) => "appletvos",
+        ("tvos", true) => "appletvsimulator",
+        ("watchos", false) => "watchos",
+        ("watchos", true) => "watchsimulator",
+        (other, _) => bail!("Don't know the SDK name for {other}"),
+    })
+}
+
+/// The `xcrun --sdk` name for a target triple's platform.
+pub(crate) fn sdk_name_for_target(target: &str) -> Result<&'static str> {
+    sdk_name(&LibraryGroupId::from_target(target)?)
+}
+
+/// Stamp `slice` with a proper `LC_BUILD_VERSION` load command via
+/// `vtool -set-build-version`, so xcodebuild classifies the library
+/// correctly even when the object files lack platform metadata.
+fn stamp_build_version(slice: &Slice, targets: Option<&DeploymentTargets>) -> Result<()> {
+    let id = LibraryGroupId::from_target(&slice.target_triple)?;
+    let platform = if id.simulator {
+        format!("{}-sim", id.os)
+    } else {
+        id.os.clone()
+    };
+    let setting = match id.os.as_str() {
+        "ios" => "IPHONEOS_DEPLOYMENT_TARGET",
+        "macos" => "MACOSX_DEPLOYMENT_TARGET",
+        "tvos" => "TVOS_DEPLOYMENT_TARGET",
+        "watchos" => "WATCHOS_DEPLOYMENT_TARGET",
+        other => bail!("Don't know the deployment target setting for {other}"),
+    };
+    let Some(minos) = targets
+        .and_then(|targets| targets.version(setting))
+        .map(str::to_string)
+        .or_else(|| std::env::var(setting).ok())
+    else {
+        bail!(
+            "Can't determine the minimum OS for {}: pass --deployment-targets-from or set {setting}",
+            slice.target_triple
+        );
+    };
+    let sdk_name = sdk_name(&id)?;
+    let sdk_version = match crate::utils::sdk_version() {
+        Some(version) => version,
+        None => {
+            let output = Command::new("xcrun")
+                .args(["--sdk", sdk_name, "--show-sdk-version"])
+                .successful_output()?;
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        }
+    };
+
+    // vtool refuses in-place edits; write next to the input and rename over.
+    let stamped = slice.library_path.with_extension("a.stamped");
+    crate::utils::xcrun_tool("vtool")
+        .args(["-set-build-version", &platform, &minos, &sdk_version])
+        .args(["-replace", "-output", stamped.as_str()])
+        .arg(&slice.library_path)
+        .successful_output()?;
+    std::fs::rename(&stamped, &slice.library_path)
+        .with_context(|| format!("Can't replace {}", slice.library_path))?;
+    Ok(())
+}
+
+/// Merge any configured prebuilt archives into `slice`'s library with
+/// `libtool -static`, so separately built C code ships in the same slice
+/// without being re-exported through cargo.
+pub(crate) fn merge_extra_archives(project: &Project, slice: Slice) -> Result<Slice> {
+    let archives = extra_archives_for(project, &slice.target_triple);
+    if archives.is_empty() {
+        return Ok(slice);
+    }
+    for archive in &archives {
+        if !archive.exists() {
+            bail!(
+                "Extra archive {archive} for {} does not exist",
+                slice.target_triple
+            );
+        }
+    }
+    let out_dir = project.tmp_dir("extra-archives");
+    std::fs::create_dir_all(&out_dir).with_context(|| format!("Can't create {out_dir}"))?;
+    let merged = out_dir.join(format!(
+        "{}-{}",
+        slice.target_triple,
+        slice.library_path.file_name().expect("libraries have a file name")
+    ));
+    let mut cmd = libtool_command();
+    cmd.args(["-static", "-o", merged.as_str()]);
+    cmd.arg(&slice.library_path);
+    for archive in &archives {
+        cmd.arg(archive);
+    }
+    cmd.successful_output()?;
+    Ok(Slice {
+        target_triple: slice.target_triple,
+        library_path: merged,
+    })
+}
+
+/// Identifies one library inside the XCFramework: a platform plus whether it
+/// is the simulator variant. Slices for the same id get lipo'd together.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct LibraryGroupId {
+    pub(crate) os: String,
+    pub(crate) simulator: bool,
+}
+
+/// Platform metadata for the triples the helper builds out of the box. The
+/// target-spec fallback in [`LibraryGroupId::from_target`] needs
+/// `RUSTC_BOOTSTRAP=1`, a nightly-only hack worth keeping off the hot path.
+const KNOWN_TARGETS: [(&str, &str, bool); 12] = [
+    ("aarch64-apple-ios", "ios", false),
+    ("aarch64-apple-ios-sim", "ios", true),
+    ("x86_64-apple-ios", "ios", true),
+    ("aarch64-apple-darwin", "macos", false),
+    ("x86_64-apple-darwin", "macos", false),
+    ("aarch64-apple-tvos", "tvos", false),
+    ("aarch64-apple-tvos-sim", "tvos", true),
+    ("x86_64-apple-tvos", "tvos", true),
+    ("arm64_32-apple-watchos", "watchos", false),
+    ("aarch64-apple-watchos", "watchos", false),
+    ("aarch64-apple-watchos-sim", "watchos", true),
+    ("x86_64-apple-watchos-sim", "watchos", true),
+];
+
+/// Results of the rustc target-spec fallback, so each unknown triple is
+/// queried at most once per run.
+static TARGET_SPEC_CACHE: std::sync::Mutex<BTreeMap<String, LibraryGroupId>> =
+    std::sync::Mutex::new(BTreeMap::new());
+
+impl LibraryGroupId {
+    /// Derive the platform for `target_triple` from the static table of
+    /// known triples, falling back to rustc's target spec (its `os` /
+    /// `llvm-target` fields) for triples we haven't seen before.
+    pub(crate) fn from_target(target_triple: &str) -> Result<Self> {
+        if let Some((_, os, simulator)) = KNOWN_TARGETS
+            .iter()
+            .find(|(triple, _, _)| *triple == target_triple)
+        {
+            return Ok(Self {
+                os: os.to_string(),
+                simulator: *simulator,
+            });
+        }
+        let mut cache = TARGET_SPEC_CACHE
+            .lock()
+            .expect("target spec cache never poisoned");
+        if let Some(id) = cache.get(target_triple) {
+            return Ok(id.clone());
+        }
+        let id = Self::from_target_spec(target_triple)?;
+        cache.insert(target_triple.to_string(), id.clone());
+        Ok(id)
+    }
+
+    /// Ask rustc for the target spec of `target_triple` and derive the
+    /// platform from its `os` / `llvm-target` fields.
+    fn from_target_spec(target_triple: &str) -> Result<Self> {
+        let output = Command::new("rustc")
+            .env("RUSTC_BOOTSTRAP", "1")
+            .args([
+                "-Z",
+                "unstable-options",
+                "--print",
+                "target-spec-json",
+                "--target",
+                target_triple,
+            ])
+            .successful_output()?;
+        let spec: serde_json::Value = serde_json::from_slice(&output.stdout)
+            .with_context(|| format!("Can't parse target spec for {target_triple}"))?;
+        let os = spec
+            .get("os")
+            .and_then(|v| v.as_str())
+            .with_context(|| format!("Target spec for {target_triple} has no `os` field"))?;
+        let llvm_target = spec
+            .get("llvm-target")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default();
+        Ok(Self {
+            os: os.to_string(),
+            simulator: llvm_target.ends_with("simulator"),
+        })
+    }
+
+    /// Directory name for this library inside the staging area, e.g.
+    /// `ios` or `ios-simulator`.
+    pub(crate) fn name(&self) -> String {
+        if self.simulator {
+            format!("{}-simulator", self.os)
+        } else {
+            self.os.clone()
+        }
+    }
+}
+
+/// All slices that end up in one library of the XCFramework.
+pub(crate) struct LibraryGroup {
+    pub(crate) id: LibraryGroupId,
+    pub(crate) slices: Vec<Slice>,
+}
+
+impl LibraryGroup {
+    /// The architectures of this group's slices, sorted.
+    fn architectures(&self) -> Vec<String> {
+        let mut archs: Vec<String> = self
+            .slices
+            .iter()
+            .map(|slice| slice.expected_architecture().to_string())
+            .collect();
+        archs.sort();
+        archs
+    }
+
+    /// The library identifier used as the directory name inside the
+    /// XCFramework, matching xcodebuild's convention:
+    /// `<platform>-<archs joined by _>[-simulator]`.
+    fn identifier(&self) -> String {
+        let mut identifier = format!("{}-{}", self.id.os, self.architectures().join("_"));
+        if self.id.simulator {
+            identifier.push_str("-simulator");
+        }
+        identifier
+    }
+
+    /// Merge the group's slices into a single `file_name` library under
+    /// `out_dir` and return its path.
+    ///
+    /// Single-slice groups (e.g. the tvOS device library) are copied as-is:
+    /// lipo would only rewrap them, and avoiding `xcrun` keeps those groups
+    /// buildable on non-mac hosts.
+    pub(crate) fn create(&self, file_name: &str, out_dir: &Utf8Path) -> Result<Utf8PathBuf> {
+        let dir = out_dir.join(self.id.name());
+        fs::recreate_dir(&dir)?;
+        let library = dir.join(file_name);
+        match self.slices.as_slice() {
+            [slice] => {
+                fs::clone_or_copy(&slice.library_path, &library)?;
+            }
+            slices => {
+                let mut cmd = lipo_command();
+                cmd.arg("-create");
+                for slice in slices {
+                    cmd.arg(&slice.library_path);
+                }
+                cmd.args(["-output", library.as_str()]);
+                cmd.successful_output()?;
+            }
+        }
+        Ok(library)
+    }
+}
+
+/// Assemble the XCFramework(s) purely from already-built per-target static
+/// libraries and binding directories, without running cargo or bindgen.
+///
+/// This is the packaging tail of `build` as its own command: CI can fan the
+/// expensive per-platform cargo builds out across matrix jobs, download the
+/// `target/<triple>/` trees as artifacts, and merge them here on one machine.
+pub fn package_xcframework(
+    platforms: &[ApplePlatform],
+    profile: &str,
+    options: &BuildOptions,
+    reporter: &Reporter,
+) -> crate::Result<()> {
+    let run = || -> Result<()> {
+        let mut project = Project::from_current_dir()?;
+        project.select_packages(&options.packages)?;
+        let targets: Vec<&str> = platforms
+            .iter()
+            .flat_map(ApplePlatform::target_triples)
+            .collect();
+        let profile_dir = crate::build::profile_dir_name(profile);
+        match options.layout {
+            FrameworkLayout::Merged => {
+                create_xcframework(&project, &targets, profile_dir, options, reporter)?;
+            }
+            FrameworkLayout::PerCrate => {
+                create_crate_xcframeworks(&project, &targets, profile_dir, options, reporter)?;
+            }
+        }
+        Ok(())
+    };
+    run().map_err(crate::Error::from)
+}
+
+/// Assemble the merged-FFI XCFramework from the libraries built for `targets`.
+pub(crate) fn create_xcframework(
+    project: &Project,
+    targets: &[&str],
+    profile_dir_name: &str,
+    options: &BuildOptions,
+    reporter: &Reporter,
+) -> Result<Utf8PathBuf> {
+    let deployment_targets = build_version_targets(options)?;
+    let groups = collect_groups(targets, |target| {
+        let slice = Slice::create(project, target, profile_dir_name, CrateType::Staticlib)?;
+        let slice = merge_extra_archives(project, slice)?;
+        if options.fix_build_version {
+            stamp_build_version(&slice, deployment_targets.as_ref())?;
+        }
+        Ok(slice)
+    })?;
+    let staging_dir = project.tmp_dir("xcframework");
+    fs::recreate_dir(&staging_dir)?;
+
+    reporter.phase_started(BuildPhase::Package, groups.len());
+    let _lock = crate::utils::WorkspaceLock::acquire(project.target_dir())?;
+    let output = assemble_xcframework(
+        project,
+        &groups,
+        Assembly {
+            staging_dir: &staging_dir,
+            output_path: project.xcframework_path(),
+            module_name: &project.ffi_module_name,
+            bindings_subdir: None,
+            crate_type: CrateType::Staticlib,
+            options,
+        },
+        reporter,
+    )?;
+    reporter.phase_finished(BuildPhase::Package);
+    Ok(output)
+}
+
+/// Assemble one XCFramework per UniFFI crate ([`FrameworkLayout::PerCrate`]),
+/// each from that crate's own static library and generated headers.
+pub(crate) fn create_crate_xcframeworks(
+    project: &Project,
+    targets: &[&str],
+    profile_dir_name: &str,
+    options: &BuildOptions,
+    reporter: &Reporter,
+) -> Result<Vec<Utf8PathBuf>> {
+    reporter.phase_started(
+        BuildPhase::Package,
+        project.uniffi_packages.len() * targets.len(),
+    );
+    let deployment_targets = build_version_targets(options)?;
+    let _lock = crate::utils::WorkspaceLock::acquire(project.target_dir())?;
+    let mut outputs = Vec::new();
+    for package in &project.uniffi_packages {
+        let groups = collect_groups(targets, |target| {
+            let slice = Slice::create_for_package(project, target, profile_dir_name, package, CrateType::Staticlib)?;
+            let slice = merge_extra_archives(project, slice)?;
+            if options.fix_build_version {
+                stamp_build_version(&slice, deployment_targets.as_ref())?;
+            }
+            Ok(slice)
+        })?;
+        let staging_dir = project
+            .tmp_dir("xcframework")
+            .join(&package.internal_module_name);
+        fs::recreate_dir(&staging_dir)?;
+        outputs.push(assemble_xcframework(
+            project,
+            &groups,
+            Assembly {
+                staging_dir: &staging_dir,
+                output_path: project.crate_xcframework_path(package),
+                module_name: &package.ffi_module_name(),
+                bindings_subdir: Some(&package.internal_module_name),
+                crate_type: CrateType::Staticlib,
+                options,
+            },
+            reporter,
+        )?);
+    }
+    reporter.phase_finished(BuildPhase::Package);
+    Ok(outputs)
+}
+
+/// Assemble both linkage variants of the merged XCFramework from one set of
+/// cargo builds: `<ffi>-static.xcframework` from the static archives and
+/// `<ffi>-dynamic.xcframework` from the cdylibs (cargo emits both in one
+/// build when `crate-type` lists them). The generated Package.swift picks a
+/// variant via the `UNIFFI_LINKAGE` environment variable.
+pub(crate) fn create_xcframework_variants(
+    project: &Project,
+    targets: &[&str],
+    profile_dir_name: &str,
+    options: &BuildOptions,
+    reporter: &Reporter,
+) -> Result<Vec<Utf8PathBuf>> {
+    let deployment_targets = build_version_targets(options)?;
+    let _lock = crate::utils::WorkspaceLock::acquire(project.target_dir())?;
+    let mut outputs = Vec::new();
+    reporter.phase_started(BuildPhase::Package, targets.len() * 2);
+    for (variant, crate_type) in [
+        ("static", CrateType::Staticlib),
+        ("dynamic", CrateType::Cdylib),
+    ] {
+        let groups = collect_groups(targets, |target| {
+            let slice = Slice::create(project, target, profile_dir_name, crate_type)?;
+            if crate_type == CrateType::Cdylib {
+                // Extra archives and build-version stamping apply to the
+                // static archives only: the cdylib got both at link time.
+                return Ok(slice);
+            }
+            let slice = merge_extra_archives(project, slice)?;
+            if options.fix_build_version {
+                stamp_build_version(&slice, deployment_targets.as_ref())?;
+            }
+            Ok(slice)
+        })?;
+        let staging_dir = project.tmp_dir("xcframework").join(variant);
+        fs::recreate_dir(&staging_dir)?;
+        outputs.push(assemble_xcframework(
+            project,
+            &groups,
+            Assembly {
+                staging_dir: &staging_dir,
+                output_path: project.variant_xcframework_path(variant),
+                module_name: &project.ffi_module_name,
+                bindings_subdir: None,
+                crate_type,
+                options,
+            },
+            reporter,
+        )?);
+    }
+    reporter.phase_finished(BuildPhase::Package);
+    Ok(outputs)
+}
+
+/// Group the slices produced by `make_slice` by platform/simulator.
+pub(crate) fn collect_groups(
+    targets: &[&str],
+    make_slice: impl Fn(&str) -> Result<Slice>,
+) -> Result<BTreeMap<LibraryGroupId, LibraryGroup>> {
+    let mut groups: BTreeMap<LibraryGroupId, LibraryGroup> = BTreeMap::new();
+    for target in targets {
+        let id = LibraryGroupId::from_target(target)?;
+        let slice = make_slice(target)?;
+        slice.validate_architecture()?;
+        groups
+            .entry(id.clone())
+            .or_insert_with(|| LibraryGroup {
+                id,
+                slices: Vec::new(),
+            })
+            .slices
+            .push(slice);
+    }
+    Ok(groups)
+}
+
+/// Where and how one XCFramework is assembled, shared by both layouts.
+struct Assembly<'a> {
+    staging_dir: &'a Utf8Path,
+    output_path: Utf8PathBuf,
+    module_name: &'a str,
+    /// Subdirectory of each target's swift-bindings output to take headers
+    /// from, for layouts where bindings are generated per crate.
+    bindings_subdir: Option<&'a str>,
+    /// Which library flavor the slices hold, deciding the merged file name.
+    crate_type: CrateType,
+    options: &'a BuildOptions,
+}
+
+/// Merge each group's slices, gather headers, and lay out the XCFramework at
+/// the assembly's output path, via `xcodebuild -create-xcframework` or (with
+/// `no_xcodebuild`) our own assembler.
+fn assemble_xcframework(
+    project: &Project,
+    groups: &BTreeMap<LibraryGroupId, LibraryGroup>,
+    assembly: Assembly<'_>,
+    reporter: &Reporter,
+) -> Result<Utf8PathBuf> {
+    let inputs_key = assembly_inputs_key(project, groups, &assembly)?;
+    let Assembly {
+        staging_dir,
+        output_path,
+        module_name,
+        bindings_subdir,
+        crate_type,
+        options,
+    } = assembly;
+    let output_path = &output_path;
+    let key_path = inputs_key_path(output_path);
+    if output_path.exists()
+        && std::fs::read_to_string(&key_path).is_ok_and(|recorded| recorded.trim() == inputs_key)
+    {
+        for group in groups.values() {
+            reporter.step_finished(BuildPhase::Package, format!("{} (cached)", group.id.name()));
+        }
+        println!("{output_path} inputs are unchanged; keeping the existing bundle");
+        return Ok(output_path.to_path_buf());
+    }
+    let no_xcodebuild = options.no_xcodebuild || options.zigbuild;
+    let previous_sizes = library_sizes(output_path).unwrap_or_default();
+    if output_path.exists() {
+        std::fs::remove_dir_all(output_path)
+            .with_context(|| format!("Can't remove {output_path}"))?;
+    }
+
+    // lipo invocations and header copies are independent per group; run them
+    // on the rayon pool and assemble the xcodebuild arguments afterwards.
+    let libraries: Vec<(Utf8PathBuf, Utf8PathBuf)> = groups
+        .values()
+        .collect::<Vec<_>>()
+        .par_iter()
+        .map(|group| {
+            let library = group.create(
+                &format!("lib{module_name}.{}", crate_type.extension()),
+                staging_dir,
+            )?;
+            crate::symbols::check_merged_library(&library)?;
+            let headers = headers_dir(
+                project,
+                group,
+                staging_dir,
+                module_name,
+                bindings_subdir,
+                reporter,
+            )?;
+            reporter.step_finished(BuildPhase::Package, group.id.name());
+            Ok((library, headers))
+        })
+        .collect::<Result<_>>()?;
+
+    if no_xcodebuild {
+        assemble_natively(groups, &libraries, output_path)?;
+    } else {
+        let mut cmd = crate::utils::xcodebuild_command();
+        cmd.arg("-create-xcframework");
+        for (library, headers) in &libraries {
+            cmd.args(["-library", library.as_str()]);
+            cmd.args(["-headers", headers.as_str()]);
+        }
+        cmd.args(["-output", output_path.as_str()]);
+        cmd.successful_output()?;
+
+        patch_xcframework(output_path)?;
+    }
+    if options.emit_module_interface {
+        emit_module_interfaces(project, groups, output_path, bindings_subdir)?;
+    }
+    report_size_changes(output_path, &previous_sizes)?;
+    std::fs::write(&key_path, format!("{inputs_key}\n"))
+        .with_context(|| format!("Can't write {key_path}"))?;
+
+    Ok(output_path.to_path_buf())
+}
+
+/// Hash of everything [`assemble_xcframework`] derives its output from: each
+/// slice's merged library and generated headers, the module name, and the
+/// layout-affecting options. When it matches the key recorded beside the
+/// existing bundle, reassembly would reproduce the same bytes, so wrapper-only
+/// iterations don't pay the lipo/xcodebuild cost again.
+fn assembly_inputs_key(
+    project: &Project,
+    groups: &BTreeMap<LibraryGroupId, LibraryGroup>,
+    assembly: &Assembly<'_>,
+) -> Result<String> {
+    let mut input = Vec::new();
+    input.extend_from_slice(env!("CARGO_PKG_VERSION").as_bytes());
+    input.extend_from_slice(assembly.module_name.as_bytes());
+    input.extend_from_slice(assembly.crate_type.extension().as_bytes());
+    input.push(assembly.options.emit_module_interface as u8);
+    input.push((assembly.options.no_xcodebuild || assembly.options.zigbuild) as u8);
+    for group in groups.values() {
+        for slice in &group.slices {
+            input.extend_from_slice(slice.target_triple.as_bytes());
+            let library = std::fs::read(&slice.library_path)
+                .with_context(|| format!("Can't read {}", slice.library_path))?;
+            input.extend_from_slice(&crate::utils::fnv1a_64(&library).to_le_bytes());
+            let mut bindings_dir = project
+                .target_dir()
+                .join(&slice.target_triple)
+                .join("swift-bindings");
+            if let Some(subdir) = assembly.bindings_subdir {
+                bindings_dir.push(subdir);
+            }
+            if bindings_dir.exists() {
+                input.extend_from_slice(&fs::tree_digest(&bindings_dir, &[])?.to_le_bytes());
+            }
+        }
+    }
+    Ok(format!("{:016x}", crate::utils::fnv1a_64(&input)))
+}
+
+/// Where an assembled XCFramework's inputs key is recorded: a sibling file,
+/// so the bundle itself stays exactly what consumers expect.
+fn inputs_key_path(output_path: &Utf8Path) -> Utf8PathBuf {
+    Utf8PathBuf::from(format!("{output_path}.inputs"))
+}
+
+/// One `AvailableLibraries` entry in the XCFramework's `Info.plist`.
+struct PlistLibrary {
+    identifier: String,
+    file_name: String,
+    architectures: Vec<String>,
+    platform: String,
+    simulator: bool,
+}
+
+#[derive(rinja::Template)]
+#[template(path = "Info.plist", escape = "none")]
+struct InfoPlist {
+    libraries: Vec<PlistLibrary>,
+}
+
+/// Lay out the XCFramework ourselves: one directory per library group, named
+/// by its identifier, plus the `Info.plist` xcodebuild would have written.
+///
+/// `-create-xcframework` does no real work for static libraries beyond this
+/// layout, and doing it natively removes the Xcode dependency (and the module
+/// map patching its output needs).
+fn assemble_natively(
+    groups: &BTreeMap<LibraryGroupId, LibraryGroup>,
+    libraries: &[(Utf8PathBuf, Utf8PathBuf)],
+    output_path: &Utf8Path,
+) -> Result<Utf8PathBuf> {
+    let mut plist_libraries = Vec::new();
+    for (group, (library, headers)) in groups.values().zip(libraries) {
+        let identifier = group.identifier();
+        let dir = output_path.join(&identifier);
+        fs::recreate_dir(&dir)?;
+        let file_name = library
+            .file_name()
+            .expect("library paths always have a file name");
+        fs::clone_or_copy(library, &dir.join(file_name))?;
+        fs::copy_dir(headers, &dir.join("Headers"))?;
+        plist_libraries.push(PlistLibrary {
+            identifier,
+            file_name: file_name.to_string(),
+            architectures: group.architectures(),
+            platform: group.id.os.clone(),
+            simulator: group.id.simulator,
+        });
+    }
+
+    use rinja::Template;
+    let contents = InfoPlist {
+        libraries: plist_libraries,
+    }
+    .render()
+    .context("Can't render Info.plist")?;
+    let plist = output_path.join("Info.plist");
+    std::fs::write(&plist, contents).with_context(|| format!("Can't write {plist}"))?;
+    Ok(output_path.to_path_buf())
+}
+
+/// Compile the generated bindings sources of each slice with library
+/// evolution and place the resulting `.swiftmodule`/`.swiftinterface` files
+/// under `Modules/<Module>.swiftmodule/<arch>.*` in the slice's library
+/// directory, mirroring how Xcode lays out binary frameworks.
+fn emit_module_interfaces(
+    project: &Project,
+    groups: &BTreeMap<LibraryGroupId, LibraryGroup>,
+    output_path: &Utf8Path,
+    bindings_subdir: Option<&str>,
+) -> Result<()> {
+    for group in groups.values() {
+        let library_dir = output_path.join(group.identifier());
+        let headers = library_dir.join("Headers");
+        for slice in &group.slices {
+            let mut bindings_dir = project
+                .target_dir()
+                .join(&slice.target_triple)
+                .join("swift-bindings");
+            if let Some(subdir) = bindings_subdir {
+                bindings_dir.push(subdir);
+            }
+            for source in fs::files_with_extension(&bindings_dir, "swift")? {
+                let module = source
+                    .file_stem()
+                    .expect("swift sources always have a file stem");
+                let module_dir = library_dir
+                    .join("Modules")
+                    .join(format!("{module}.swiftmodule"));
+                std::fs::create_dir_all(&module_dir)
+                    .with_context(|| format!("Can't create {module_dir}"))?;
+                let arch = slice.expected_architecture();
+                crate::utils::xcrun_tool("swiftc")
+                    .args(["-emit-module", "-parse-as-library"])
+                    .args(["-enable-library-evolution"])
+                    .args(["-module-name", module])
+                    .args(["-target", &swift_target(&slice.target_triple)])
+                    .args(["-I", headers.as_str()])
+                    .args([
+                        "-emit-module-path",
+                        module_dir.join(format!("{arch}.swiftmodule")).as_str(),
+                    ])
+                    .args([
+                        "-emit-module-interface-path",
+                        module_dir.join(format!("{arch}.swiftinterface")).as_str(),
+                    ])
+                    .arg(&source)
+                    .successful_output()
+                    .with_context(|| format!("Can't emit module interface for {source}"))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The Swift target triple for a Rust target triple: `aarch64` becomes
+/// `arm64`, `darwin` becomes `macosx`, and the `-sim` suffix is spelled out.
+fn swift_target(rust_triple: &str) -> String {
+    let mut triple = rust_triple.replace("darwin", "macosx");
+    if let Some(arch) = triple.strip_prefix("aarch64") {
+        triple = format!("arm64{arch}");
+    }
+    if let Some(device) = triple.strip_suffix("-sim") {
+        triple = format!("{device}-simulator");
+    }
+    triple
+}
+
+/// Size in bytes of each library in the XCFramework, keyed by the library
+/// directory name (e.g. `ios-arm64`).
+fn library_sizes(xcframework: &Utf8Path) -> Result<BTreeMap<String, u64>> {
+    let mut sizes = BTreeMap::new();
+    for entry in xcframework.read_dir_utf8()? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        for library in fs::files_with_extension(entry.path(), "a")? {
+            sizes.insert(entry.file_name().to_string(), library.metadata()?.len());
+        }
+    }
+    Ok(sizes)
+}
+
+/// Print how each library's size changed relative to the previous build, so
+/// the effect of e.g. dead-code stripping options is visible.
+fn report_size_changes(xcframework: &Utf8Path, previous: &BTreeMap<String, u64>) -> Result<()> {
+    for (library, size) in library_sizes(xcframework)? {
+        match previous.get(&library) {
+            Some(&old) if old != size => {
+                let percent = (size as f64 - old as f64) / old as f64 * 100.0;
+                println!(
+                    "{library}: {} (was {}, {percent:+.1}%)",
+                    indicatif::HumanBytes(size),
+                    indicatif::HumanBytes(old),
+                );
+            }
+            _ => println!("{library}: {}", indicatif::HumanBytes(size)),
+        }
+    }
+    Ok(())
+}
+
+/// Collect the generated C headers and module map for `group` into a
+/// `Headers` directory next to its merged library.
+fn headers_dir(
+    project: &Project,
+    group: &LibraryGroup,
+    staging_dir: &Utf8Path,
+    module_name: &str,
+    bindings_subdir: Option<&str>,
+    reporter: &Reporter,
+) -> Result<Utf8PathBuf> {
+    let slice = group
+        .slices
+        .first()
+        .expect("library groups are never empty");
+    let mut bindings_dir = project
+        .target_dir()
+        .join(&slice.target_triple)
+        .join("swift-bindings");
+    if let Some(subdir) = bindings_subdir {
+        bindings_dir.push(subdir);
+    }
+    let headers = staging_dir.join(group.id.name()).join("Headers");
+    fs::recreate_dir(&headers)?;
+    for header in fs::files_with_extension(&bindings_dir, "h")? {
+        let bytes = std::fs::copy(&header, headers.join(header.file_name().unwrap()))
+            .with_context(|| format!("Can't copy {header}"))?;
+        reporter.bytes_copied(bytes);
+    }
+    write_modulemap(project, module_name, &headers)?;
+    Ok(headers)
+}
+
+#[derive(rinja::Template)]
+#[template(path = "module.modulemap", escape = "none")]
+struct ModuleMap<'a> {
+    ffi_module_name: &'a str,
+    header_files: Vec<String>,
+    submodules: Vec<Submodule>,
+    export_all: bool,
+    link_libraries: &'a [String],
+    link_frameworks: &'a [String],
+}
+
+/// One submodule of the generated module map, holding a single UniFFI
+/// crate's headers in the per-crate layout.
+#[derive(serde::Serialize)]
+struct Submodule {
+    name: String,
+    header_files: Vec<String>,
+}
+
+/// Write `module.modulemap` next to the copied headers.
+///
+/// With `modulemap_layout = "per-crate"` each UniFFI crate's headers go into
+/// their own submodule, so Objective-C consumers can `#include` them
+/// individually; headers not attributable to a crate stay on the top level.
+///
+/// Projects can override the embedded template via the `modulemap_template`
+/// key in `uniffi.toml` (e.g. to add `link` declarations); custom templates
+/// get the same context variables (`ffi_module_name`, `header_files`,
+/// `submodules`, `export_all`, `link_libraries`, `link_frameworks`).
+fn write_modulemap(project: &Project, module_name: &str, headers: &Utf8Path) -> Result<()> {
+    let mut header_files: Vec<String> = fs::files_with_extension(headers, "h")?
+        .iter()
+        .filter_map(|h| h.file_name().map(str::to_string))
+        .collect();
+    let mut submodules = Vec::new();
+    if project.modulemap_layout == ModulemapLayout::PerCrate {
+        for package in &project.uniffi_packages {
+            // uniffi-bindgen names each header after the crate's bindings
+            // module, so a prefix match attributes it.
+            let (own, rest) = header_files
+                .into_iter()
+                .partition(|header| header.starts_with(&package.internal_module_name));
+            header_files = rest;
+            if !own.is_empty() {
+                submodules.push(Submodule {
+                    name: package.internal_module_name.clone(),
+                    header_files: own,
+                });
+            }
+        }
+    }
+    let contents = match &project.modulemap_template {
+        Some(template_path) => {
+            let template = std::fs::read_to_string(template_path)
+                .with_context(|| format!("Can't read modulemap template {template_path}"))?;
+            minijinja::Environment::new()
+                .render_str(
+                    &template,
+                    minijinja::context! {
+                        ffi_module_name => module_name,
+                        header_files => header_files,
+                        submodules => submodules,
+                        export_all => project.modulemap_export_all,
+                        link_libraries => project.link_libraries,
+                        link_frameworks => project.link_frameworks,
+                    },
+                )
+                .with_context(|| format!("Can't render modulemap template {template_path}"))?
+        }
+        None => {
+            use rinja::Template;
+            ModuleMap {
+                ffi_module_name: module_name,
+                header_files,
+                submodules,
+                export_all: project.modulemap_export_all,
+                link_libraries: &project.link_libraries,
+                link_frameworks: &project.link_frameworks,
+            }
+            .render()
+            .context("Can't render module.modulemap")?
+        }
+    };
+    let path = headers.join("module.modulemap");
+    std::fs::write(&path, contents).with_context(|| format!("Can't write {path}"))?;
+    Ok(())
+}
+
+/// `xcodebuild -create-xcframework` has been observed to drop the module map
+/// from the per-library Headers directory on some Xcode versions. Copy it back
+/// in from the sibling library that has one.
+fn patch_xcframework(xcframework: &Utf8Path) -> Result<()> {
+    let mut modulemap: Option<Utf8PathBuf> = None;
+    let mut missing: Vec<Utf8PathBuf> = Vec::new();
+    for entry in xcframework.read_dir_utf8()? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        let headers = entry.path().join("Headers");
+        if !headers.exists() {
+            continue;
+        }
+        let map = headers.join("module.modulemap");
+        if map.exists() {
+            modulemap.get_or_insert(map);
+        } else {
+            missing.push(map);
+        }
+    }
+    if let Some(source) = modulemap {
+        for destination in missing {
+            std::fs::copy(&source, &destination)
+                .with_context(|| format!("Can't copy {source} to {destination}"))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expected_architectures() {
+        let slice = |triple: &str| Slice {
+            target_triple: triple.to_string(),
+            library_path: Utf8PathBuf::from("lib.a"),
+        };
+        assert_eq!(slice("aarch64-apple-ios").expected_architecture(), "arm64");
+        assert_eq!(
+            slice("arm64_32-apple-watchos").expected_architecture(),
+            "arm64_32"
+        );
+        assert_eq!(
+            slice("x86_64-apple-darwin").expected_architecture(),
+            "x86_64"
+        );
+    }
+
+    #[test]
+    fn library_group_names() {
+        let device = LibraryGroupId {
+            os: "ios".to_string(),
+            simulator: false,
+        };
+        let sim = LibraryGroupId {
+            os: "ios".to_string(),
+            simulator: true,
+        };
+        assert_eq!(device.name(), "ios");
+        assert_eq!(sim.name(), "ios-simulator");
+    }
+
+    #[test]
+    fn known_targets_cover_every_platform_triple() {
+        for platform in ApplePlatform::all() {
+            for triple in platform.target_triples() {
+                assert!(
+                    KNOWN_TARGETS.iter().any(|(known, _, _)| *known == triple),
+                    "{triple} is missing from KNOWN_TARGETS"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn swift_targets() {
+        assert_eq!(swift_target("aarch64-apple-ios"), "arm64-apple-ios");
+        assert_eq!(
+            swift_target("aarch64-apple-ios-sim"),
+            "arm64-apple-ios-simulator"
+        );
+        assert_eq!(swift_target("x86_64-apple-darwin"), "x86_64-apple-macosx");
+        assert_eq!(
+            swift_target("arm64_32-apple-watchos"),
+            "arm64_32-apple-watchos"
+        );
+    }
+
+    #[test]
+    fn library_group_identifiers() {
+        let slice = |triple: &str| Slice {
+            target_triple: triple.to_string(),
+            library_path: Utf8PathBuf::from("lib.a"),
+        };
+        let group = LibraryGroup {
+            id: LibraryGroupId {
+                os: "ios".to_string(),
+                simulator: true,
+            },
+            slices: vec![slice("x86_64-apple-ios"), slice("aarch64-apple-ios-sim")],
+        };
+        assert_eq!(group.identifier(), "ios-arm64_x86_64-simulator");
+    }
+}
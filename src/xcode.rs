@@ -0,0 +1,163 @@
+//! Xcode selection and version verification.
+
+
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use camino::Utf8Path;
+
+use crate::utils::ExecuteCommand;
+use crate::xcframework::ApplePlatform;
+
+/// Pin the Xcode used by every xcodebuild/xcrun invocation by exporting
+/// `DEVELOPER_DIR`, the variable xcode-select honours. Subprocesses inherit
+/// our environment, so setting it once covers the whole run.
+pub fn set_developer_dir(path: &Utf8Path) {
+    std::env::set_var("DEVELOPER_DIR", path);
+}
+
+/// Use this Swift toolchain for every xcrun/xcodebuild/swift invocation, by
+/// exporting `TOOLCHAINS` — the variable those tools honour for identifiers
+/// like `org.swift.600202405011a` (from a toolchain's Info.plist).
+pub fn set_toolchain(identifier: &str) {
+    std::env::set_var("TOOLCHAINS", identifier);
+}
+
+/// The `SDKROOT` the Rust build for `target` should use, when `--sdk-version`
+/// asked for a specific SDK; `None` leaves the toolchain default in place.
+/// cc and rustc pass the path to the Apple linker.
+pub(crate) fn sdkroot_for_target(target: &str) -> Result<Option<camino::Utf8PathBuf>> {
+    let Some(version) = crate::utils::sdk_version() else {
+        return Ok(None);
+    };
+    let sdk = format!("{}{version}", crate::xcframework::sdk_name_for_target(target)?);
+    let output = Command::new("xcrun")
+        .args(["--sdk", &sdk, "--show-sdk-path"])
+        .successful_output()
+        .with_context(|| format!("Can't locate the {sdk} SDK"))?;
+    let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok(Some(path.into()))
+}
+
+/// The minimum Xcode version able to build for `platform` with the SDKs the
+/// current target triples link against. Raise per platform as requirements
+/// diverge (e.g. a future visionOS target would need Xcode 15+).
+fn minimum_xcode_version(platform: ApplePlatform) -> (u32, u32) {
+    match platform {
+        ApplePlatform::IOS | ApplePlatform::MacOS => (15, 0),
+        ApplePlatform::TvOS | ApplePlatform::WatchOS => (15, 0),
+    }
+}
+
+/// Fail early when the selected Xcode is too old for any requested platform,
+/// instead of surfacing an SDK error halfway through packaging.
+pub(crate) fn check_xcode_version(platforms: &[ApplePlatform]) -> Result<()> {
+    if crate::utils::dry_run() {
+        return Ok(());
+    }
+    let output = crate::utils::xcodebuild_command()
+        .arg("-version")
+        .successful_output()?;
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    let version = parse_xcode_version(&stdout)
+        .with_context(|| format!("Can't parse `xcodebuild -version` output: {stdout:?}"))?;
+    for platform in platforms {
+        let minimum = minimum_xcode_version(*platform);
+        if version < minimum {
+            bail!(
+                "Building for {} needs Xcode {}.{} or newer, but the selected Xcode is {}.{}. \
+                 Select a newer one with --developer-dir or xcode-select.",
+                platform.name(),
+                minimum.0,
+                minimum.1,
+                version.0,
+                version.1,
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The platform name `simctl list runtimes` uses for a platform's simulator
+/// runtime, or `None` when the platform doesn't need one.
+fn simulator_runtime_name(platform: ApplePlatform) -> Option<&'static str> {
+    match platform {
+        ApplePlatform::MacOS => None,
+        ApplePlatform::IOS => Some("iOS"),
+        ApplePlatform::TvOS => Some("tvOS"),
+        ApplePlatform::WatchOS => Some("watchOS"),
+    }
+}
+
+/// Warn when a requested platform has no installed simulator runtime, before
+/// xcodebuild errors late when tests resolve their destination. A missing
+/// runtime doesn't stop the SDK-only Rust build, so this warns rather than
+/// fails.
+pub(crate) fn check_simulator_runtimes(platforms: &[ApplePlatform]) -> Result<()> {
+    if crate::utils::dry_run() {
+        return Ok(());
+    }
+    let output = Command::new("xcrun")
+        .args(["simctl", "list", "runtimes"])
+        .successful_output()
+        .context("Can't list simulator runtimes with `xcrun simctl list runtimes`")?;
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    for platform in platforms {
+        let Some(name) = simulator_runtime_name(*platform) else {
+            continue;
+        };
+        if !runtime_available(&stdout, name) {
+            eprintln!(
+                "Warning: no {name} simulator runtime is installed; simulator tests for {} \
+                 will fail. Install one with `xcodebuild -downloadPlatform {name}`.",
+                platform.name()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Whether the `simctl list runtimes` output lists an available runtime for
+/// `name`, e.g. `iOS 17.5 (17.5 - 21F79) - com.apple.CoreSimulator.…`.
+fn runtime_available(output: &str, name: &str) -> bool {
+    output.lines().any(|line| {
+        let line = line.trim();
+        line.strip_prefix(name)
+            .is_some_and(|rest| rest.starts_with(' ') && !line.contains("unavailable"))
+    })
+}
+
+/// Extract (major, minor) from the first line of `xcodebuild -version`
+/// output, e.g. `Xcode 15.4`.
+fn parse_xcode_version(output: &str) -> Option<(u32, u32)> {
+    let version = output.lines().next()?.strip_prefix("Xcode ")?.trim();
+    let mut components = version.split('.');
+    let major = components.next()?.parse().ok()?;
+    let minor = components.next().and_then(|m| m.parse().ok()).unwrap_or(0);
+    Some((major, minor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_xcodebuild_version_output() {
+        assert_eq!(
+            parse_xcode_version("Xcode 15.4\nBuild version 15F31d\n"),
+            Some((15, 4))
+        );
+        assert_eq!(parse_xcode_version("Xcode 16\n"), Some((16, 0)));
+        assert_eq!(parse_xcode_version("not xcode"), None);
+    }
+
+    #[test]
+    fn detects_installed_simulator_runtimes() {
+        let output = "== Runtimes ==\n\
+            iOS 17.5 (17.5 - 21F79) - com.apple.CoreSimulator.SimRuntime.iOS-17-5\n\
+            watchOS 10.5 (10.5 - 21T575) - (unavailable, The runtime profile could not be found)\n";
+        assert!(runtime_available(output, "iOS"));
+        assert!(!runtime_available(output, "watchOS"));
+        assert!(!runtime_available(output, "tvOS"));
+    }
+}
@@ -0,0 +1,27 @@
+// Generated by uniffi-swift-helper as a starting point; edit freely.
+
+import SwiftUI
+{%- for product in products %}
+import {{ product }}
+{%- endfor %}
+
+@main
+struct {{ name }}ExampleApp: App {
+    var body: some Scene {
+        WindowGroup {
+            ContentView()
+        }
+    }
+}
+
+struct ContentView: View {
+    var body: some View {
+        VStack(spacing: 12) {
+            Text("{{ name }} example")
+                .font(.title)
+            Text("The generated bindings are linked. Edit ContentView to call into them.")
+        }
+        .padding()
+        .frame(minWidth: 420, minHeight: 200)
+    }
+}
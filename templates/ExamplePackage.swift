@@ -0,0 +1,24 @@
+// swift-tools-version: {{ tools_version }}
+
+// Generated by uniffi-swift-helper. A minimal runnable consumer of the
+// SDK package in the parent directory.
+
+import PackageDescription
+
+let package = Package(
+    name: "{{ name }}Example",
+    platforms: [.macOS(.v13)],
+    dependencies: [
+        .package(path: ".."),
+    ],
+    targets: [
+        .executableTarget(
+            name: "{{ name }}Example",
+            dependencies: [
+                {%- for product in products %}
+                .product(name: "{{ product }}", package: "{{ name }}"),
+                {%- endfor %}
+            ]
+        ),
+    ]
+)
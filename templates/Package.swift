@@ -0,0 +1,39 @@
+// swift-tools-version: {{ tools_version }}
+
+// Generated by uniffi-swift-helper. Do not edit by hand:
+// run `uniffi-swift-helper generate-package` instead.
+{%- for pin in pinned_revisions %}
+// {{ pin.0 }} resolved from git revision {{ pin.1 }}.
+{%- endfor %}
+
+import PackageDescription
+{%- if linkage_selection %}
+import Foundation
+
+// UNIFFI_LINKAGE=dynamic links the dynamic XCFramework variant; anything
+// else (or unset) links the static one.
+let linkage = ProcessInfo.processInfo.environment["UNIFFI_LINKAGE"] == "dynamic" ? "dynamic" : "static"
+{%- endif %}
+
+let package = Package(
+    name: "{{ name }}",
+    {%- if !platforms.is_empty() %}
+    platforms: [
+        {%- for platform in platforms %}
+        {{ platform }},
+        {%- endfor %}
+    ],
+    {%- endif %}
+    products: [
+        {%- for product in products %}
+        {{ product }},
+        {%- endfor %}
+    ],
+    targets: [
+        {%- for target in targets %}
+        {{ target }},
+        {%- endfor %}
+    ]{%- if let Some(version) = language_version %},
+    swiftLanguageVersions: [.version("{{ version }}")]
+    {%- endif %}
+)
@@ -0,0 +1,32 @@
+// Generated by uniffi-swift-helper. Do not edit by hand:
+// run `uniffi-swift-helper generate-tuist` instead.
+
+import ProjectDescription
+
+/// Dependencies on the {{ name }} SDK, for Tuist project descriptions.
+///
+/// Declare the wrapper package on the project first:
+///
+///     packages: [.local(path: "{{ package_path }}")]
+///
+/// then add `{{ name }}Dependencies.wrappers` to a target's dependencies.
+/// All paths are relative to the SDK repository root; prefix them when this
+/// file lives elsewhere.
+public enum {{ name }}Dependencies {
+    /// The generated Swift wrapper products. These pull in the prebuilt
+    /// binaries through the package, so most targets need nothing else.
+    public static let wrappers: [TargetDependency] = [
+        {%- for product in products %}
+        .package(product: "{{ product }}"),
+        {%- endfor %}
+    ]
+
+    /// The prebuilt Rust core alone, for targets that call the FFI directly
+    /// without the Swift wrappers. Don't combine with `wrappers`, or the
+    /// library gets linked twice.
+    public static let binaries: [TargetDependency] = [
+        {%- for path in xcframeworks %}
+        .xcframework(path: "{{ path }}"),
+        {%- endfor %}
+    ]
+}
@@ -0,0 +1,19 @@
+// This file was processed by uniffi-swift-helper. Do not edit by hand.
+
+{%- for module in ffi_modules %}
+#if canImport({{ module }})
+    import {{ module }}
+#endif
+{%- endfor %}
+{%- for module in internal_modules %}
+import {{ module }}
+{%- endfor %}
+{%- for module in external_modules %}
+import {{ module }}
+{%- endfor %}
+{%- for module in extra_imports %}
+import {{ module }}
+{%- endfor %}
+{%- for external in external_types %}
+typealias {{ external.name }} = {{ external.module }}.{{ external.name }}
+{%- endfor %}